@@ -0,0 +1,153 @@
+//! Implements the [`AtmosphereDispersion`], which perturbs the temperature,
+//! pressure and density of any atmosphere model. This allows to study the
+//! influence of the atmospheric uncertainty.
+//!
+//! Each variable is multiplied with a constant factor, a table over the
+//! altitude and a random profile. The random profile consists of normally
+//! distributed relative perturbations, which are drawn every correlation
+//! length and interpolated linearly. It is reproducible with the same seed.
+
+use crate::state::State;
+use crate::utils::{random, Table1D};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Represents the dispersions of the temperature, pressure and density.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AtmosphereDispersion {
+    /// Dispersion of the temperature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<Dispersion>,
+    /// Dispersion of the pressure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<Dispersion>,
+    /// Dispersion of the density.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub density: Option<Dispersion>,
+}
+
+/// Represents the dispersion of one variable. The multipliers are combined.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Dispersion {
+    /// Constant multiplier.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub factor: Option<f64>,
+    /// Table of the multiplier, usually over the `"altitude"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub table: Option<Table1D>,
+    /// Random relative perturbation over the altitude.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub random: Option<RandomProfile>,
+}
+
+/// Represents a random relative perturbation over the altitude.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RandomProfile {
+    /// Seed of the random numbers. The same seed results in the same profile.
+    pub seed: u64,
+    /// Standard deviation of the relative perturbation, e.g. `0.1` for 10 %.
+    pub standard_deviation: f64,
+    /// Altitude difference in m between the independent perturbations.
+    pub correlation_length: f64,
+}
+
+impl RandomProfile {
+    /// Calculates the relative perturbation at the altitude `alt`. Below zero,
+    /// the perturbation at zero is used.
+    pub fn perturbation(&self, alt: f64) -> f64 {
+        let position = alt.max(0.) / self.correlation_length;
+        let index = position.floor();
+        let fraction = position - index;
+
+        let lower = random::normal(self.seed, index as u64);
+        let upper = random::normal(self.seed, index as u64 + 1);
+
+        self.standard_deviation * (lower + fraction * (upper - lower))
+    }
+}
+
+impl Dispersion {
+    /// Calculates the multiplier of the variable.
+    pub fn multiplier(&self, state: &State) -> f64 {
+        let mut multiplier = self.factor.unwrap_or(1.);
+        if let Some(table) = &self.table {
+            multiplier *= table.at_state(state);
+        }
+        if let Some(random) = &self.random {
+            multiplier *= 1. + random.perturbation(state.altitude);
+        }
+        multiplier
+    }
+}
+
+impl AtmosphereDispersion {
+    /// Calculates the multiplier of the temperature.
+    pub fn temperature(&self, state: &State) -> f64 {
+        multiplier(&self.temperature, state)
+    }
+
+    /// Calculates the multiplier of the pressure.
+    pub fn pressure(&self, state: &State) -> f64 {
+        multiplier(&self.pressure, state)
+    }
+
+    /// Calculates the multiplier of the density.
+    pub fn density(&self, state: &State) -> f64 {
+        multiplier(&self.density, state)
+    }
+}
+
+/// Helper function to calculate the multiplier of an optional dispersion.
+fn multiplier(dispersion: &Option<Dispersion>, state: &State) -> f64 {
+    match dispersion {
+        Some(dispersion) => dispersion.multiplier(state),
+        None => 1.,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_almost_eq_rel;
+
+    #[test]
+    fn multiplier() {
+        let dispersion: AtmosphereDispersion = serde_json::from_value(serde_json::json!({
+            "density": {
+                "factor": 1.1,
+                "table": { "x": ["altitude", [0, 1e4]], "data": [1, 2] },
+            },
+            "pressure": {
+                "random": { "seed": 1, "standard_deviation": 0.1, "correlation_length": 1e3 }
+            }
+        }))
+        .unwrap();
+
+        let state = State {
+            altitude: 5e3,
+            ..Default::default()
+        };
+        assert_almost_eq_rel!(dispersion.density(&state), 1.65_f64, 1e-12);
+        assert_eq!(dispersion.temperature(&state), 1.);
+
+        // The random profile is reproducible and interpolated between the
+        // perturbations
+        let random = dispersion.pressure.as_ref().unwrap().random.unwrap();
+        assert_eq!(dispersion.pressure(&state), 1. + 0.1 * random::normal(1, 5));
+        let state = State {
+            altitude: 5.5e3,
+            ..Default::default()
+        };
+        assert_almost_eq_rel!(
+            random.perturbation(state.altitude),
+            0.05 * (random::normal(1, 5) + random::normal(1, 6)),
+            1e-12
+        );
+
+        let other = RandomProfile { seed: 2, ..random };
+        assert_ne!(other.perturbation(5e3), random.perturbation(5e3));
+    }
+}
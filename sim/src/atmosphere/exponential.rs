@@ -0,0 +1,35 @@
+//! Implements an isothermal exponential atmosphere. It is a simple fit of the
+//! atmosphere of other planets, e.g. Mars and Venus.
+//!
+//! The pressure and density decrease with `exp(-h / H)`, where `H` is the
+//! scale height and `h` the altitude above the surface.
+
+use super::GasProperties;
+use serde::Serialize;
+
+/// Represents an isothermal exponential atmosphere.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ExponentialAtmosphere {
+    /// The pressure at the surface in Pa.
+    pub surface_pressure: f64,
+    /// The constant temperature in K.
+    pub temperature: f64,
+    /// The scale height in m.
+    pub scale_height: f64,
+    /// The properties of the atmospheric gas.
+    pub gas: GasProperties,
+}
+
+impl ExponentialAtmosphere {
+    /// Calculates the atmospheric pressure in Pa at the altitude `alt`.
+    pub fn pressure(&self, alt: f64) -> f64 {
+        self.surface_pressure * f64::exp(-alt / self.scale_height)
+    }
+
+    /// Calculates the atmospheric density in kg/m^3 at the altitude `alt`.
+    ///
+    /// Uses the ideal gas law `rho = P / (R * T)`.
+    pub fn density(&self, alt: f64) -> f64 {
+        self.pressure(alt) / (self.gas.gas_constant * self.temperature)
+    }
+}
@@ -0,0 +1,284 @@
+//! Implements the [`Gusts`], a model of the atmospheric turbulence with the
+//! Dryden or von Kármán spectra of MIL-F-8785C.
+//!
+//! The turbulence is a frozen random field in the planet frame, which the
+//! vehicle flies through. Each component in the local north-east-up frame is
+//! a sum of sinusoids with random directions, wavenumbers and phases, whose
+//! amplitudes follow the spectrum. The gusts are therefore only a function of
+//! the position, so they are deterministic in the stages of the integrator and
+//! reproducible with the same seed.
+//!
+//! As the waves have random directions, the spectrum along the flight path
+//! only approximates the given one, but the intensity and the scale length are
+//! retained. As above 2000 ft in MIL-F-8785C, the horizontal components use
+//! the longitudinal spectrum with the scale length, the vertical component the
+//! transverse spectrum with half of the scale length.
+
+use super::wind::north_east_up_to_planet;
+use crate::state::State;
+use crate::utils::{random, Table1D};
+use nalgebra::{vector, Vector3};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{PI, TAU};
+
+/// The number of sinusoids per component.
+const WAVES: usize = 64;
+/// The range of the wavenumbers relative to the inverse scale length.
+const WAVENUMBER_RANGE: (f64, f64) = (1e-2, 1e2);
+
+/// Represents the turbulence spectrum.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Spectrum {
+    /// The Dryden spectrum.
+    #[default]
+    Dryden,
+    /// The von Kármán spectrum.
+    VonKarman,
+}
+
+/// Represents a sinusoid of the turbulence field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Wave {
+    /// The wave vector in rad/m in the planet frame.
+    wave_vector: Vector3<f64>,
+    /// The phase in rad.
+    phase: f64,
+    /// The amplitude relative to the intensity.
+    amplitude: f64,
+}
+
+/// Represents the gusts.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(from = "GustsUnchecked")]
+pub struct Gusts {
+    /// The turbulence spectrum.
+    spectrum: Spectrum,
+    /// The seed of the random numbers.
+    seed: u64,
+    /// The table of the intensity in m/s.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    intensity: Option<Table1D>,
+    /// The scale length in m.
+    scale_length: f64,
+    /// The sinusoids of the north, east and up component.
+    #[serde(skip)]
+    waves: [Vec<Wave>; 3],
+}
+
+/// The configuration of the [`Gusts`].
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GustsUnchecked {
+    /// The turbulence spectrum. Defaults to `"dryden"`.
+    #[serde(default)]
+    spectrum: Spectrum,
+    /// Seed of the random numbers. The same seed results in the same gusts.
+    /// Defaults to 0.
+    #[serde(default)]
+    seed: u64,
+    /// Table of the intensity, i.e. the standard deviation of each component
+    /// in m/s, usually over the `"altitude"`. Without it, there are no gusts.
+    intensity: Option<Table1D>,
+    /// Scale length in m. Defaults to 533.4 m (1750 ft).
+    scale_length: Option<f64>,
+}
+
+impl From<GustsUnchecked> for Gusts {
+    fn from(value: GustsUnchecked) -> Self {
+        let scale_length = value.scale_length.unwrap_or(533.4);
+        let (horizontal, vertical) = ((scale_length, false), (scale_length / 2., true));
+
+        let waves = [horizontal, horizontal, vertical]
+            .iter()
+            .enumerate()
+            .map(|(i, (scale_length, transverse))| {
+                waves(value.spectrum, *scale_length, *transverse, value.seed, i)
+            })
+            .collect::<Vec<_>>()
+            .try_into()
+            .expect("There are three components");
+
+        Self {
+            spectrum: value.spectrum,
+            seed: value.seed,
+            intensity: value.intensity,
+            scale_length,
+            waves,
+        }
+    }
+}
+
+impl JsonSchema for Gusts {
+    fn schema_name() -> String {
+        "Gusts".into()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        GustsUnchecked::json_schema(gen)
+    }
+}
+
+impl Gusts {
+    /// Calculates the gust velocity in m/s in the planet frame.
+    ///
+    /// Uses the position in the planet frame, the altitude, the geodetic
+    /// latitude and the longitude of the state.
+    pub fn gust_planet(&self, state: &State) -> Vector3<f64> {
+        let Some(intensity) = &self.intensity else {
+            return Vector3::zeros();
+        };
+        let intensity = intensity.at_state(state);
+        if intensity == 0. {
+            return Vector3::zeros();
+        }
+
+        let [north, east, up] = self.waves.each_ref().map(|waves| {
+            intensity
+                * waves
+                    .iter()
+                    .map(|wave| {
+                        wave.amplitude
+                            * (wave.wave_vector.dot(&state.position_planet) + wave.phase).cos()
+                    })
+                    .sum::<f64>()
+        });
+
+        north_east_up_to_planet(state, north, east, up)
+    }
+}
+
+/// Calculates the one-sided spectrum with unit intensity at the wavenumber in
+/// rad/m.
+fn spectral_density(
+    spectrum: Spectrum,
+    scale_length: f64,
+    transverse: bool,
+    wavenumber: f64,
+) -> f64 {
+    let x = scale_length * wavenumber;
+    match (spectrum, transverse) {
+        (Spectrum::Dryden, false) => 2. * scale_length / PI / (1. + x.powi(2)),
+        (Spectrum::Dryden, true) => {
+            scale_length / PI * (1. + 3. * x.powi(2)) / (1. + x.powi(2)).powi(2)
+        }
+        (Spectrum::VonKarman, false) => {
+            2. * scale_length / PI / (1. + (1.339 * x).powi(2)).powf(5. / 6.)
+        }
+        (Spectrum::VonKarman, true) => {
+            scale_length / PI * (1. + 8. / 3. * (1.339 * x).powi(2))
+                / (1. + (1.339 * x).powi(2)).powf(11. / 6.)
+        }
+    }
+}
+
+/// Creates the sinusoids of a component with unit intensity.
+///
+/// The wavenumbers are spaced logarithmically, each randomly within its
+/// interval. The amplitudes are normalized, as the spectrum is truncated. The
+/// index of the component separates the random numbers of the components.
+fn waves(
+    spectrum: Spectrum,
+    scale_length: f64,
+    transverse: bool,
+    seed: u64,
+    component: usize,
+) -> Vec<Wave> {
+    let (min, max) = (
+        WAVENUMBER_RANGE.0 / scale_length,
+        WAVENUMBER_RANGE.1 / scale_length,
+    );
+    let step = (max / min).ln() / WAVES as f64;
+
+    let mut waves: Vec<Wave> = (0..WAVES)
+        .map(|i| {
+            let index = 4 * (component * WAVES + i) as u64;
+            let lower = min * (i as f64 * step).exp();
+            let upper = min * ((i + 1) as f64 * step).exp();
+            let wavenumber = lower * (random::uniform(seed, index) * step).exp();
+
+            // Uniformly distributed direction
+            let z = 2. * random::uniform(seed, index + 1) - 1.;
+            let azimuth = TAU * random::uniform(seed, index + 2);
+            let direction = vector![
+                (1. - z.powi(2)).sqrt() * azimuth.cos(),
+                (1. - z.powi(2)).sqrt() * azimuth.sin(),
+                z
+            ];
+
+            Wave {
+                wave_vector: wavenumber * direction,
+                phase: TAU * random::uniform(seed, index + 3),
+                amplitude: f64::sqrt(
+                    2. * spectral_density(spectrum, scale_length, transverse, wavenumber)
+                        * (upper - lower),
+                ),
+            }
+        })
+        .collect();
+
+    // The variance of the sum is half of the sum of the squared amplitudes
+    let variance: f64 = waves.iter().map(|wave| wave.amplitude.powi(2) / 2.).sum();
+    for wave in waves.iter_mut() {
+        wave.amplitude /= variance.sqrt();
+    }
+
+    waves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_almost_eq_rel;
+
+    fn gusts(spectrum: &str, seed: u64) -> Gusts {
+        serde_json::from_value(serde_json::json!({
+            "spectrum": spectrum,
+            "seed": seed,
+            "intensity": { "x": ["altitude", [0, 1e4]], "data": [10, 0] },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn gust_planet() {
+        for spectrum in ["dryden", "von_karman"] {
+            let gusts = gusts(spectrum, 1);
+
+            // Sample along a line through the field at 5 km altitude
+            let samples: Vec<Vector3<f64>> = (0..20000)
+                .map(|i| {
+                    gusts.gust_planet(&State {
+                        altitude: 5e3,
+                        position_planet: vector![6.378e6, 10. * i as f64, 0.],
+                        ..Default::default()
+                    })
+                })
+                .collect();
+
+            // The intensity is half of the table value
+            let variance =
+                samples.iter().map(|gust| gust.norm_squared()).sum::<f64>() / samples.len() as f64;
+            assert_almost_eq_rel!(variance, 3. * 25_f64, 0.2);
+        }
+
+        // Deterministic and reproducible, but different for other seeds
+        let state = State {
+            altitude: 5e3,
+            position_planet: vector![6.378e6, 1e3, 2e3],
+            ..Default::default()
+        };
+        let gust = gusts("dryden", 1).gust_planet(&state);
+        assert_eq!(gusts("dryden", 1).gust_planet(&state), gust);
+        assert_ne!(gusts("dryden", 2).gust_planet(&state), gust);
+
+        // No gusts without intensity
+        let state = State {
+            altitude: 1e4,
+            ..state
+        };
+        assert_eq!(gusts("dryden", 1).gust_planet(&state), Vector3::zeros());
+        assert_eq!(Gusts::default().gust_planet(&state), Vector3::zeros());
+    }
+}
@@ -0,0 +1,508 @@
+//! Implements the Jacchia 1971 thermosphere model, according to Jacchia:
+//! "Revised Static Models of the Thermosphere and Exosphere with Empirical
+//! Temperature Profiles", SAO Special Report 332, 1971. Like NRLMSISE-00, it
+//! depends on the solar and geomagnetic activity, as well as on the position
+//! relative to the Sun.
+//!
+//! The exospheric temperature is calculated from the 10.7 cm solar flux, the
+//! diurnal variation with the local solar time and latitude, and the
+//! geomagnetic index. It defines the temperature profile above 90 km. Up to
+//! 100 km, the atmosphere is mixed. Above, each species (N2, O2, O, Ar, He,
+//! and H above 500 km) is in diffusive equilibrium. Finally, the semiannual
+//! and seasonal-latitudinal variations are applied.
+//!
+//! Below 90 km, the 1976 U.S. Standard Atmosphere is used. There is a small
+//! jump of the density of about 1 % at 90 km.
+//!
+//! The position relative to the Sun is calculated from the position in the
+//! planet frame and the Greenwich mean sidereal time, so it does not depend on
+//! the orientation of the planet frame. The model is only valid for the Earth.
+
+use super::standard_atmosphere_1976;
+use crate::epoch::tt_to_utc;
+use crate::orientation::greenwich_mean_sidereal_time;
+use crate::state::State;
+use crate::third_body::ThirdBody;
+use crate::utils::constants::{
+    AIR_KAPPA, GAS_CONSTANT, OBLIQUITY_J2000, STD_GRAVITY, US76_EARTH_RADIUS,
+};
+use nalgebra::Vector3;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI, TAU};
+
+/// The lower boundary of the model in km.
+const LOWER_ALTITUDE: f64 = 90.;
+/// The altitude in km above which the species are in diffusive equilibrium.
+const DIFFUSION_ALTITUDE: f64 = 100.;
+/// The altitude in km of the inflection point of the temperature profile.
+const INFLECTION_ALTITUDE: f64 = 125.;
+/// The altitude in km above which hydrogen is considered.
+const HYDROGEN_ALTITUDE: f64 = 500.;
+
+/// The temperature in K at 90 km.
+const LOWER_TEMPERATURE: f64 = 183.;
+/// The density in kg/m^3 at 90 km.
+const LOWER_DENSITY: f64 = 3.46e-6;
+
+/// The mean molecular weight in kg/kmol at sea level.
+const SEA_LEVEL_MOLECULAR_WEIGHT: f64 = 28.960;
+/// The Avogadro constant in 1/kmol.
+const AVOGADRO_CONSTANT: f64 = 6.022045e26;
+
+/// The coefficients of the polynomial of the mean molecular weight in kg/kmol
+/// between 90 and 100 km.
+const MOLECULAR_WEIGHT: [f64; 7] = [
+    -435093.363387,
+    28275.5646391,
+    -765.33466108,
+    11.043387545,
+    -0.08958790995,
+    0.00038737586,
+    -0.000000697444,
+];
+
+/// The coefficients of the polynomial of the temperature between 90 and
+/// 125 km.
+const TEMPERATURE: [f64; 5] = [-89284375., 3542400., -52687.5, 340.5, -0.8];
+
+/// The species in diffusive equilibrium. The values are the molecular weight
+/// in kg/kmol, the thermal diffusion factor and the volume fraction at sea
+/// level.
+const SPECIES: [(f64, f64, f64); 5] = [
+    (28.0134, 0., 0.78110),     // N2
+    (31.9988, 0., 0.20955),     // O2
+    (15.9994, 0., 0.),          // O
+    (39.948, 0., 0.009343),     // Ar
+    (4.0026, -0.38, 6.1471e-6), // He
+];
+
+/// The molecular weight in kg/kmol and the thermal diffusion factor of
+/// hydrogen.
+const HYDROGEN: (f64, f64) = (1.00797, -0.38);
+
+/// Represents the Jacchia 1971 model with the solar and geomagnetic activity.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Jacchia1971 {
+    /// The 10.7 cm solar flux of the previous day in 1e-22 W/(m^2 Hz).
+    pub solar_flux: f64,
+    /// The 81-day average of the 10.7 cm solar flux in 1e-22 W/(m^2 Hz).
+    pub solar_flux_average: f64,
+    /// The planetary geomagnetic index Kp of 6.7 hours before.
+    pub geomagnetic_index: f64,
+}
+
+/// Represents the atmospheric conditions at a position.
+struct Conditions {
+    /// The temperature in K.
+    temperature: f64,
+    /// The density in kg/m^3.
+    density: f64,
+    /// The mean molecular weight in kg/kmol.
+    molecular_weight: f64,
+}
+
+impl Jacchia1971 {
+    /// Calculates the atmospheric temperature in K.
+    ///
+    /// Uses the altitude and the inertial position of the state at the Julian
+    /// date (TT).
+    pub fn temperature(&self, state: &State, julian_date: f64) -> f64 {
+        if state.altitude < LOWER_ALTITUDE * 1e3 {
+            return standard_atmosphere_1976::temperature(state.altitude);
+        }
+        let exospheric_temperature = self.exospheric_temperature(state, julian_date).0;
+        temperature(state.altitude / 1e3, exospheric_temperature)
+    }
+
+    /// Calculates the atmospheric pressure in Pa.
+    ///
+    /// Uses the altitude and the inertial position of the state at the Julian
+    /// date (TT).
+    pub fn pressure(&self, state: &State, julian_date: f64) -> f64 {
+        if state.altitude < LOWER_ALTITUDE * 1e3 {
+            return standard_atmosphere_1976::pressure(state.altitude);
+        }
+        let conditions = self.conditions(state, julian_date);
+        conditions.density * GAS_CONSTANT * conditions.temperature / conditions.molecular_weight
+    }
+
+    /// Calculates the atmospheric density in kg/m^3.
+    ///
+    /// Uses the altitude and the inertial position of the state at the Julian
+    /// date (TT).
+    pub fn density(&self, state: &State, julian_date: f64) -> f64 {
+        if state.altitude < LOWER_ALTITUDE * 1e3 {
+            return standard_atmosphere_1976::density(state.altitude);
+        }
+        self.conditions(state, julian_date).density
+    }
+
+    /// Calculates the speed of sound in m/s. The ratio of specific heats of
+    /// air is used at all altitudes.
+    ///
+    /// Uses the altitude and the inertial position of the state at the Julian
+    /// date (TT).
+    pub fn speed_of_sound(&self, state: &State, julian_date: f64) -> f64 {
+        if state.altitude < LOWER_ALTITUDE * 1e3 {
+            return standard_atmosphere_1976::speed_of_sound(state.altitude);
+        }
+        let conditions = self.conditions(state, julian_date);
+        f64::sqrt(AIR_KAPPA * GAS_CONSTANT * conditions.temperature / conditions.molecular_weight)
+    }
+
+    /// Calculates the exospheric temperature in K, as well as the correction
+    /// of the logarithm of the density due to the geomagnetic activity [eq.
+    /// 14 ff.].
+    fn exospheric_temperature(&self, state: &State, julian_date: f64) -> (f64, f64) {
+        // Nighttime minimum
+        let minimum_temperature = 379.
+            + 3.24 * self.solar_flux_average
+            + 1.3 * (self.solar_flux - self.solar_flux_average);
+
+        // Diurnal variation with the hour angle of the Sun
+        let (declination, right_ascension) =
+            vehicle_declination_right_ascension(state, julian_date);
+        let (sun_declination, sun_right_ascension) =
+            declination_right_ascension(&ThirdBody::Sun.position(julian_date));
+        let hour_angle = right_ascension - sun_right_ascension;
+
+        let theta = 0.5 * (declination + sun_declination).abs();
+        let eta = 0.5 * (declination - sun_declination).abs();
+        let tau = (hour_angle - 37_f64.to_radians()
+            + 6_f64.to_radians() * (hour_angle + 43_f64.to_radians()).sin()
+            + PI)
+            .rem_euclid(TAU)
+            - PI;
+
+        let sin_theta = theta.sin().powf(2.2);
+        let local_temperature = minimum_temperature
+            * (1.
+                + 0.3
+                    * (sin_theta + (eta.cos().powf(2.2) - sin_theta) * (0.5 * tau).cos().powi(3)));
+
+        // Geomagnetic activity
+        let kp = self.geomagnetic_index;
+        if state.altitude < 200e3 {
+            (
+                local_temperature + 14. * kp + 0.02 * kp.exp(),
+                0.012 * kp + 1.2e-5 * kp.exp(),
+            )
+        } else {
+            (local_temperature + 28. * kp + 0.03 * kp.exp(), 0.)
+        }
+    }
+
+    /// Calculates the atmospheric conditions above 90 km.
+    fn conditions(&self, state: &State, julian_date: f64) -> Conditions {
+        let alt = state.altitude / 1e3;
+        let (exospheric_temperature, geomagnetic_correction) =
+            self.exospheric_temperature(state, julian_date);
+        let temperature = |alt: f64| temperature(alt, exospheric_temperature);
+
+        // Mixed atmosphere up to 100 km [eq. 4]
+        let mixed_density = |alt: f64| {
+            let integral = simpson(
+                |alt| {
+                    1e3 * mean_molecular_weight(alt) * gravity(alt)
+                        / (GAS_CONSTANT * temperature(alt))
+                },
+                LOWER_ALTITUDE,
+                alt,
+            );
+            LOWER_DENSITY * mean_molecular_weight(alt) / mean_molecular_weight(LOWER_ALTITUDE)
+                * LOWER_TEMPERATURE
+                / temperature(alt)
+                * f64::exp(-integral)
+        };
+
+        let (density, molecular_weight) = if alt <= DIFFUSION_ALTITUDE {
+            (mixed_density(alt), mean_molecular_weight(alt))
+        } else {
+            // Number densities at 100 km from the dissociation of O2 [eq. 5]
+            let molecular_weight = mean_molecular_weight(DIFFUSION_ALTITUDE);
+            let number_density =
+                mixed_density(DIFFUSION_ALTITUDE) * AVOGADRO_CONSTANT / molecular_weight;
+            let ratio = molecular_weight / SEA_LEVEL_MOLECULAR_WEIGHT;
+            let base_density = |i: usize| match i {
+                1 => number_density * (ratio * (1. + SPECIES[1].2) - 1.),
+                2 => 2. * number_density * (1. - ratio),
+                _ => number_density * ratio * SPECIES[i].2,
+            };
+
+            // Diffusive equilibrium above 100 km [eq. 6]
+            let diffusion_integral = |from: f64, to: f64| {
+                simpson(
+                    |alt| 1e3 * gravity(alt) / (GAS_CONSTANT * temperature(alt)),
+                    from,
+                    to,
+                )
+            };
+            let integral = diffusion_integral(DIFFUSION_ALTITUDE, alt);
+            let temperature_ratio = temperature(DIFFUSION_ALTITUDE) / temperature(alt);
+
+            let mut number_densities: Vec<(f64, f64)> = SPECIES
+                .iter()
+                .enumerate()
+                .map(|(i, (molecular_weight, thermal_diffusion, _))| {
+                    (
+                        *molecular_weight,
+                        base_density(i)
+                            * temperature_ratio.powf(1. + thermal_diffusion)
+                            * f64::exp(-molecular_weight * integral),
+                    )
+                })
+                .collect();
+
+            // Seasonal-latitudinal variation of helium [eq. 24]
+            let (declination, _) = vehicle_declination_right_ascension(state, julian_date);
+            let (sun_declination, _) =
+                declination_right_ascension(&ThirdBody::Sun.position(julian_date));
+            if sun_declination != 0. {
+                number_densities[4].1 *= 10_f64.powf(
+                    0.65 * (sun_declination / OBLIQUITY_J2000.to_radians()).abs()
+                        * ((FRAC_PI_4 - 0.5 * declination * sun_declination.signum())
+                            .sin()
+                            .powi(3)
+                            - 0.35355),
+                );
+            }
+
+            // Hydrogen above 500 km [eq. 7]
+            if alt > HYDROGEN_ALTITUDE {
+                let log_temperature = exospheric_temperature.log10();
+                let base_density = 1e6
+                    * 10_f64.powf(73.13 - 39.4 * log_temperature + 5.5 * log_temperature.powi(2));
+                number_densities.push((
+                    HYDROGEN.0,
+                    base_density
+                        * (temperature(HYDROGEN_ALTITUDE) / temperature(alt)).powf(1. + HYDROGEN.1)
+                        * f64::exp(-HYDROGEN.0 * diffusion_integral(HYDROGEN_ALTITUDE, alt)),
+                ));
+            }
+
+            let number_density: f64 = number_densities.iter().map(|(_, n)| n).sum();
+            let density: f64 = number_densities
+                .iter()
+                .map(|(molecular_weight, n)| molecular_weight * n)
+                .sum();
+            (density / AVOGADRO_CONSTANT, density / number_density)
+        };
+
+        // Semiannual and seasonal-latitudinal variations [eq. 21 ff.]
+        let (declination, _) = vehicle_declination_right_ascension(state, julian_date);
+        let correction = geomagnetic_correction
+            + semiannual_variation(alt, julian_date)
+            + seasonal_latitudinal_variation(alt, declination, julian_date);
+
+        Conditions {
+            temperature: temperature(alt),
+            density: density * 10_f64.powf(correction),
+            molecular_weight,
+        }
+    }
+}
+
+/// Calculates the temperature in K at the altitude `alt` in km above 90 km
+/// with the exospheric temperature [eq. 9 ff.].
+fn temperature(alt: f64, exospheric_temperature: f64) -> f64 {
+    let inflection_temperature = 371.6678 + 0.0518806 * exospheric_temperature
+        - 294.3505 * f64::exp(-0.00216222 * exospheric_temperature);
+    let difference = inflection_temperature - LOWER_TEMPERATURE;
+
+    if alt <= INFLECTION_ALTITUDE {
+        let polynomial: f64 = TEMPERATURE
+            .iter()
+            .enumerate()
+            .map(|(i, coeff)| coeff * alt.powi(i as i32))
+            .sum();
+        inflection_temperature + difference / 35_f64.powi(4) * polynomial
+    } else {
+        let gradient = 1.9 * difference / 35.;
+        let amplitude = exospheric_temperature - inflection_temperature;
+        let height = alt - INFLECTION_ALTITUDE;
+        inflection_temperature
+            + amplitude / FRAC_PI_2
+                * f64::atan(
+                    FRAC_PI_2 * gradient / amplitude * height * (1. + 4.5e-6 * height.powf(2.5)),
+                )
+    }
+}
+
+/// Calculates the mean molecular weight in kg/kmol at the altitude `alt` in
+/// km between 90 and 100 km [eq. 1].
+fn mean_molecular_weight(alt: f64) -> f64 {
+    MOLECULAR_WEIGHT
+        .iter()
+        .enumerate()
+        .map(|(i, coeff)| coeff * alt.powi(i as i32))
+        .sum()
+}
+
+/// Calculates the gravity in m/s^2 at the altitude `alt` in km.
+fn gravity(alt: f64) -> f64 {
+    STD_GRAVITY / (1. + alt * 1e3 / US76_EARTH_RADIUS).powi(2)
+}
+
+/// Calculates the declination and right ascension in rad of the position.
+fn declination_right_ascension(position: &Vector3<f64>) -> (f64, f64) {
+    (
+        position.z.atan2(position.x.hypot(position.y)),
+        position.y.atan2(position.x),
+    )
+}
+
+/// Calculates the declination and right ascension in rad of the vehicle from
+/// its position in the planet frame and the Greenwich mean sidereal time at
+/// the Julian date (TT). Precession and nutation are neglected.
+fn vehicle_declination_right_ascension(state: &State, julian_date: f64) -> (f64, f64) {
+    let (declination, longitude) = declination_right_ascension(&state.position_planet);
+    (
+        declination,
+        longitude + greenwich_mean_sidereal_time(tt_to_utc(julian_date)),
+    )
+}
+
+/// Calculates the years since 1958-01-01, which is used as reference for the
+/// semiannual and seasonal variations.
+fn years_since_1958(julian_date: f64) -> f64 {
+    (julian_date - 2400000.5 - 36204.) / 365.2422
+}
+
+/// Calculates the semiannual variation of the logarithm of the density
+/// [eq. 21].
+fn semiannual_variation(alt: f64, julian_date: f64) -> f64 {
+    let years = years_since_1958(julian_date);
+    let tau = years + 0.09544 * ((0.5 + 0.5 * (TAU * years + 6.035).sin()).powf(1.65) - 0.5);
+
+    let amplitude = (5.876e-7 * alt.powf(2.331) + 0.06328) * f64::exp(-0.002868 * alt);
+    let variation = 0.02835
+        + 0.3817 * (1. + 0.4671 * (TAU * tau + 4.137).sin()) * (2. * TAU * tau + 4.259).sin();
+
+    amplitude * variation
+}
+
+/// Calculates the seasonal-latitudinal variation of the logarithm of the
+/// density in the lower thermosphere at the declination [eq. 23].
+fn seasonal_latitudinal_variation(alt: f64, declination: f64, julian_date: f64) -> f64 {
+    let height = alt - LOWER_ALTITUDE;
+    let amplitude = 0.014 * height * f64::exp(-0.0013 * height.powi(2));
+    let season = (TAU * years_since_1958(julian_date) + 1.72).sin();
+
+    amplitude * season * declination.sin().powi(2) * declination.signum()
+}
+
+/// Integrates the function from `from` to `to` with Simpson's rule and steps
+/// of at most 2 km.
+fn simpson(function: impl Fn(f64) -> f64, from: f64, to: f64) -> f64 {
+    if to <= from {
+        return 0.;
+    }
+    let steps = 2 * ((to - from) / 4.).ceil() as usize;
+    let step = (to - from) / steps as f64;
+
+    let sum: f64 = (1..steps)
+        .map(|i| {
+            let weight = if i % 2 == 1 { 4. } else { 2. };
+            weight * function(from + i as f64 * step)
+        })
+        .sum();
+
+    (function(from) + sum + function(to)) * step / 3.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_almost_eq_rel;
+    use crate::utils::constants::JULIAN_DATE_J2000;
+
+    const MODEL: Jacchia1971 = Jacchia1971 {
+        solar_flux: 150.,
+        solar_flux_average: 150.,
+        geomagnetic_index: 0.,
+    };
+
+    /// Returns the state at the altitude, in the direction of the Sun rotated
+    /// by the angle about the z-axis. Only the position in the planet frame is
+    /// set, which is rotated by the Greenwich mean sidereal time.
+    fn state(alt: f64, angle: f64) -> State {
+        let sun = ThirdBody::Sun.position(JULIAN_DATE_J2000).normalize();
+        let sidereal_time = greenwich_mean_sidereal_time(tt_to_utc(JULIAN_DATE_J2000));
+        let direction =
+            nalgebra::Rotation3::from_axis_angle(&Vector3::z_axis(), angle - sidereal_time) * sun;
+        State {
+            altitude: alt,
+            position_planet: direction * (6.378e6 + alt),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn temperature_profile() {
+        // Temperature at 90 km, continuous at 125 km and approaching the
+        // exospheric temperature
+        assert_almost_eq_rel!(temperature(90., 1000.), LOWER_TEMPERATURE, 1e-9);
+        assert_almost_eq_rel!(
+            temperature(125. - 1e-9, 1000.),
+            temperature(125., 1000.),
+            1e-9
+        );
+        assert_almost_eq_rel!(temperature(1000., 1000.), 1000_f64, 1e-3);
+
+        // Mean molecular weight is continuous at 100 km
+        let state = state(100e3, 0.);
+        let above = State {
+            altitude: 100e3 + 1e-3,
+            ..state.clone()
+        };
+        assert_almost_eq_rel!(
+            MODEL.density(&state, JULIAN_DATE_J2000),
+            MODEL.density(&above, JULIAN_DATE_J2000),
+            1e-6
+        );
+    }
+
+    #[test]
+    fn density() {
+        // Same as the 1976 U.S. Standard Atmosphere below 90 km
+        let state_ = state(50e3, 0.);
+        assert_eq!(
+            MODEL.density(&state_, JULIAN_DATE_J2000),
+            standard_atmosphere_1976::density(50e3)
+        );
+
+        // Similar to the 1976 U.S. Standard Atmosphere for moderate activity
+        assert_almost_eq_rel!(
+            MODEL.density(&state(400e3, PI), JULIAN_DATE_J2000),
+            standard_atmosphere_1976::density(400e3),
+            0.5
+        );
+
+        // The density is higher at daytime and with higher activity
+        let day = MODEL.density(&state(400e3, 0.5), JULIAN_DATE_J2000);
+        let night = MODEL.density(&state(400e3, PI), JULIAN_DATE_J2000);
+        assert!(day > 2. * night);
+
+        let active = Jacchia1971 {
+            solar_flux: 250.,
+            solar_flux_average: 250.,
+            geomagnetic_index: 5.,
+        };
+        assert!(active.density(&state(400e3, PI), JULIAN_DATE_J2000) > 3. * night);
+
+        // Ideal gas
+        let state_ = state(300e3, 0.);
+        assert_almost_eq_rel!(
+            MODEL.pressure(&state_, JULIAN_DATE_J2000),
+            MODEL.density(&state_, JULIAN_DATE_J2000)
+                * GAS_CONSTANT
+                * MODEL.temperature(&state_, JULIAN_DATE_J2000)
+                / MODEL
+                    .conditions(&state_, JULIAN_DATE_J2000)
+                    .molecular_weight,
+            1e-9
+        );
+    }
+}
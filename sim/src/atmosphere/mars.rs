@@ -0,0 +1,65 @@
+//! Implements a model of the Mars atmosphere. It is the fit of the
+//! measurements of the Mars Global Surveyor in April 1996 by the NASA Glenn
+//! Research Center, see <https://www.grc.nasa.gov/www/k-12/airplane/atmosmrm.html>.
+//!
+//! The temperature decreases linearly with two different gradients below and
+//! above 7 km. Above 50 km, where the fit falls below the observed
+//! mesospheric temperatures, the temperature is held constant. The pressure
+//! decreases exponentially with a scale height of about 11 km.
+//!
+//! The gas is carbon dioxide. All functions take the altitude in m.
+
+use super::CARBON_DIOXIDE;
+
+/// The altitude in m at which the temperature gradient changes.
+const GRADIENT_ALTITUDE: f64 = 7e3;
+/// The altitude in m above which the temperature is constant.
+const ISOTHERMAL_ALTITUDE: f64 = 50e3;
+
+/// Calculates the atmospheric temperature in K at the altitude `alt`.
+pub fn temperature(alt: f64) -> f64 {
+    let alt = alt.min(ISOTHERMAL_ALTITUDE);
+    let celsius = if alt < GRADIENT_ALTITUDE {
+        -31. - 0.000998 * alt
+    } else {
+        -23.4 - 0.00222 * alt
+    };
+    celsius + 273.15
+}
+
+/// Calculates the atmospheric pressure in Pa at the altitude `alt`.
+pub fn pressure(alt: f64) -> f64 {
+    699. * f64::exp(-0.00009 * alt)
+}
+
+/// Calculates the atmospheric density in kg/m^3 at the altitude `alt`.
+///
+/// Uses the ideal gas law `rho = P / (R * T)`.
+pub fn density(alt: f64) -> f64 {
+    pressure(alt) / (CARBON_DIOXIDE.gas_constant * temperature(alt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_almost_eq_rel;
+
+    #[test]
+    fn mars() {
+        // Surface conditions of about 242 K, 699 Pa and 0.015 kg/m^3
+        assert_almost_eq_rel!(temperature(0.), 242.15_f64, 1e-9);
+        assert_almost_eq_rel!(pressure(0.), 699_f64, 1e-9);
+        assert_almost_eq_rel!(density(0.), 0.0153_f64, 0.01);
+
+        // The fit jumps by about 1 K at 7 km and is constant above 50 km
+        assert_almost_eq_rel!(
+            temperature(GRADIENT_ALTITUDE - 1e-6),
+            temperature(GRADIENT_ALTITUDE),
+            5e-3
+        );
+        assert_eq!(temperature(60e3), temperature(ISOTHERMAL_ALTITUDE));
+
+        // Speed of sound of CO2 of about 243 m/s at the surface
+        assert_almost_eq_rel!(CARBON_DIOXIDE.speed_of_sound(242.15), 243_f64, 0.01);
+    }
+}
@@ -5,11 +5,35 @@
 //! Defines the [`Atmosphere`] struct, which handles all functions
 //! regarding the atmosphere.
 
+mod dispersion;
+mod exponential;
+mod gusts;
+mod jacchia_1971;
+mod mars;
 mod standard_atmosphere_1962;
+mod standard_atmosphere_1976;
+mod table;
+mod wind;
 
-use crate::config::AtmosphereConfig;
+use crate::config::{AtmosphereConfig, AtmosphereModelConfig};
+use crate::planet::Planet;
 use crate::state::State;
-use nalgebra::Vector3;
+use crate::utils::constants::{
+    AIR_GAS_CONSTANT, AIR_KAPPA, AIR_SUTHERLAND_CONSTANT, AIR_SUTHERLAND_TEMPERATURE,
+    CO2_GAS_CONSTANT, CO2_KAPPA, CO2_SUTHERLAND_CONSTANT, CO2_SUTHERLAND_TEMPERATURE,
+    JULIAN_DATE_J2000, SECONDS_PER_DAY,
+};
+use nalgebra::{Rotation3, Vector3};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+pub use dispersion::AtmosphereDispersion;
+pub use exponential::ExponentialAtmosphere;
+pub use gusts::Gusts;
+pub use jacchia_1971::Jacchia1971;
+pub use table::TableAtmosphere;
+pub use wind::WindProfile;
 
 /// Represents the atmosphere. If the [`AtmosphereModel`] is set to
 /// [`AtmosphereModel::NoAtmosphere`], no atmosphere is modeled.
@@ -19,18 +43,124 @@ use nalgebra::Vector3;
 ///
 /// The methods take the entire state as input, as other atmosphere models
 /// might need the altitude instead of the geopotential altitude.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Atmosphere {
     /// Static wind vector in m/s.
     wind: Vector3<f64>,
+    /// Wind relative to the planet surface over the altitude.
+    wind_profile: WindProfile,
+    /// Turbulent gusts relative to the wind.
+    gusts: Gusts,
     /// Atmosphere model used.
     model: AtmosphereModel,
+    /// Dispersions of the atmosphere model.
+    dispersion: AtmosphereDispersion,
+    /// Whether the atmosphere model of the planet is used.
+    #[serde(skip)]
+    use_planet_model: bool,
+    /// The epoch as Julian date (TT), needed for the position of the Sun.
+    #[serde(skip)]
+    epoch: f64,
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Self {
+            wind: Vector3::zeros(),
+            wind_profile: WindProfile::default(),
+            gusts: Gusts::default(),
+            model: AtmosphereModel::default(),
+            dispersion: AtmosphereDispersion::default(),
+            use_planet_model: false,
+            epoch: JULIAN_DATE_J2000,
+        }
+    }
+}
+
+/// Represents the properties of the atmospheric gas.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GasProperties {
+    /// The ratio of specific heats.
+    pub kappa: f64,
+    /// The specific gas constant in J/(kg K).
+    pub gas_constant: f64,
+    /// The constant of Sutherland's law in kg/(m s K^0.5). Defaults to the
+    /// one of air.
+    #[serde(default = "air_sutherland_constant")]
+    pub sutherland_constant: f64,
+    /// The temperature of Sutherland's law in K. Defaults to the one of air.
+    #[serde(default = "air_sutherland_temperature")]
+    pub sutherland_temperature: f64,
+}
+
+/// Helper function for the default of [`GasProperties::sutherland_constant`].
+fn air_sutherland_constant() -> f64 {
+    AIR_SUTHERLAND_CONSTANT
+}
+
+/// Helper function for the default of
+/// [`GasProperties::sutherland_temperature`].
+fn air_sutherland_temperature() -> f64 {
+    AIR_SUTHERLAND_TEMPERATURE
+}
+
+impl Default for GasProperties {
+    fn default() -> Self {
+        AIR
+    }
 }
 
+impl GasProperties {
+    /// Calculates the speed of sound in m/s at the temperature in K.
+    ///
+    /// Uses `C_s = (kappa * R * T)^0.5`.
+    pub fn speed_of_sound(&self, temperature: f64) -> f64 {
+        f64::sqrt(self.kappa * self.gas_constant * temperature)
+    }
+
+    /// Calculates the dynamic viscosity in Pa s at the temperature in K.
+    ///
+    /// Uses Sutherland's law `mu = beta * T^1.5 / (T + S)`.
+    pub fn viscosity(&self, temperature: f64) -> f64 {
+        self.sutherland_constant * temperature.powf(1.5)
+            / (temperature + self.sutherland_temperature)
+    }
+
+    /// Calculates the mean free path in m from the viscosity in Pa s, the
+    /// temperature in K and the pressure in Pa.
+    ///
+    /// Uses the kinetic theory of gases `lambda = mu / P * (pi * R * T / 2)^0.5`.
+    /// Without pressure, it is infinite.
+    pub fn mean_free_path(&self, viscosity: f64, temperature: f64, pressure: f64) -> f64 {
+        if pressure <= 0. {
+            return f64::INFINITY;
+        }
+        viscosity / pressure * f64::sqrt(PI * self.gas_constant * temperature / 2.)
+    }
+}
+
+/// The properties of air.
+pub const AIR: GasProperties = GasProperties {
+    kappa: AIR_KAPPA,
+    gas_constant: AIR_GAS_CONSTANT,
+    sutherland_constant: AIR_SUTHERLAND_CONSTANT,
+    sutherland_temperature: AIR_SUTHERLAND_TEMPERATURE,
+};
+
+/// The properties of carbon dioxide.
+pub const CARBON_DIOXIDE: GasProperties = GasProperties {
+    kappa: CO2_KAPPA,
+    gas_constant: CO2_GAS_CONSTANT,
+    sutherland_constant: CO2_SUTHERLAND_CONSTANT,
+    sutherland_temperature: CO2_SUTHERLAND_TEMPERATURE,
+};
+
 /// Represents the different atmosphere models.
 ///
 /// More models can be added in the future.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AtmosphereModel {
     /// Use no atmosphere. This sets the temperature, pressure, density, speed
     /// of sound and dynamic pressure to zero, and the mach number to infinity.
@@ -38,72 +168,190 @@ pub enum AtmosphereModel {
     NoAtmosphere,
     /// Use the 1962 U.S. Standard Atmosphere model, defined in
     /// [standard_atmosphere_1962].
+    #[serde(rename = "standard_atmosphere_1962")]
     StandardAtmosphere1962,
+    /// Use the 1976 U.S. Standard Atmosphere model up to 1000 km, defined in
+    /// [standard_atmosphere_1976].
+    #[serde(rename = "standard_atmosphere_1976")]
+    StandardAtmosphere1976,
+    /// Use an isothermal exponential atmosphere, defined in [exponential].
+    Exponential(ExponentialAtmosphere),
+    /// Use the Mars atmosphere model, defined in [mars].
+    Mars,
+    /// Use the Jacchia 1971 thermosphere model, defined in [jacchia_1971].
+    #[serde(rename = "jacchia_1971")]
+    Jacchia1971(Jacchia1971),
+    /// Use an atmosphere given by tables, defined in [table].
+    Table(Box<TableAtmosphere>),
+}
+
+impl AtmosphereModel {
+    /// Returns the properties of the atmospheric gas. The standard atmospheres
+    /// and the Jacchia 1971 model use air at sea level.
+    pub fn gas_properties(&self) -> GasProperties {
+        match self {
+            AtmosphereModel::Exponential(model) => model.gas,
+            AtmosphereModel::Mars => CARBON_DIOXIDE,
+            AtmosphereModel::Table(model) => model.gas,
+            AtmosphereModel::NoAtmosphere
+            | AtmosphereModel::StandardAtmosphere1962
+            | AtmosphereModel::StandardAtmosphere1976
+            | AtmosphereModel::Jacchia1971(_) => AIR,
+        }
+    }
 }
 
 impl Atmosphere {
     /// Updates itself with the new configuration parameters.
-    pub fn update_with_config(&mut self, config: &AtmosphereConfig) {
-        if let Some(config) = config.enabled {
-            if config {
-                self.model = AtmosphereModel::StandardAtmosphere1962;
-            } else {
-                self.model = AtmosphereModel::NoAtmosphere;
-            }
+    ///
+    /// If [`AtmosphereModelConfig::Planet`] is selected, the atmosphere model
+    /// of the planet is used.
+    pub fn update_with_config(&mut self, config: &AtmosphereConfig, planet: &Planet) {
+        if let Some(config) = &config.model {
+            self.use_planet_model = matches!(config, AtmosphereModelConfig::Planet);
+            self.model = match config {
+                AtmosphereModelConfig::None => AtmosphereModel::NoAtmosphere,
+                AtmosphereModelConfig::Planet => planet.atmosphere_model.clone(),
+                AtmosphereModelConfig::StandardAtmosphere1962 => {
+                    AtmosphereModel::StandardAtmosphere1962
+                }
+                AtmosphereModelConfig::StandardAtmosphere1976 => {
+                    AtmosphereModel::StandardAtmosphere1976
+                }
+                AtmosphereModelConfig::Mars => AtmosphereModel::Mars,
+                AtmosphereModelConfig::Jacchia1971(model) => AtmosphereModel::Jacchia1971(*model),
+                AtmosphereModelConfig::Table(table) => {
+                    AtmosphereModel::Table(Box::new(table.clone()))
+                }
+            };
         }
         if let Some(config) = config.wind {
             self.wind = config;
         }
+        if let Some(config) = &config.wind_profile {
+            self.wind_profile = config.clone();
+        }
+        if let Some(config) = &config.gusts {
+            self.gusts = config.clone();
+        }
+        if let Some(config) = &config.dispersion {
+            self.dispersion = config.clone();
+        }
+    }
+
+    /// Updates the epoch as Julian date (TT).
+    pub fn update_with_epoch(&mut self, epoch: f64) {
+        self.epoch = epoch;
+    }
+
+    /// Updates the atmosphere model after the planet changed. If the
+    /// atmosphere model of the planet is used, it is replaced by the one of
+    /// the new planet.
+    pub fn update_with_planet(&mut self, planet: &Planet) {
+        if self.use_planet_model {
+            self.model = planet.atmosphere_model.clone();
+        }
     }
 }
 
 impl Atmosphere {
-    /// Get the atmospheric temperature in K.
-    ///
-    /// Uses the geopotential altitude of the state.
+    /// Get the atmospheric temperature in K, including the dispersion.
     pub fn temperature(&self, state: &State) -> f64 {
-        match self.model {
+        self.model_temperature(state) * self.dispersion.temperature(state)
+    }
+
+    /// Get the atmospheric temperature in K of the model.
+    ///
+    /// Uses the geopotential altitude or the altitude of the state, depending
+    /// on the model.
+    fn model_temperature(&self, state: &State) -> f64 {
+        match &self.model {
             AtmosphereModel::StandardAtmosphere1962 => {
                 standard_atmosphere_1962::temperature(state.altitude_geopotential)
             }
+            AtmosphereModel::StandardAtmosphere1976 => {
+                standard_atmosphere_1976::temperature(state.altitude)
+            }
+            AtmosphereModel::Exponential(model) => model.temperature,
+            AtmosphereModel::Mars => mars::temperature(state.altitude),
+            AtmosphereModel::Jacchia1971(model) => {
+                model.temperature(state, self.julian_date(state))
+            }
+            AtmosphereModel::Table(model) => model.temperature(state),
             AtmosphereModel::NoAtmosphere => 0.,
         }
     }
 
-    /// Get the atmospheric pressure in Pa.
+    /// Get the atmospheric pressure in Pa, including the dispersion.
     ///
-    /// Uses the geopotential altitude of the state.
+    /// Uses the geopotential altitude or the altitude of the state, depending
+    /// on the model.
     pub fn pressure(&self, state: &State) -> f64 {
-        match self.model {
+        let pressure = match &self.model {
             AtmosphereModel::StandardAtmosphere1962 => {
                 standard_atmosphere_1962::pressure(state.altitude_geopotential)
             }
+            AtmosphereModel::StandardAtmosphere1976 => {
+                standard_atmosphere_1976::pressure(state.altitude)
+            }
+            AtmosphereModel::Exponential(model) => model.pressure(state.altitude),
+            AtmosphereModel::Mars => mars::pressure(state.altitude),
+            AtmosphereModel::Jacchia1971(model) => model.pressure(state, self.julian_date(state)),
+            AtmosphereModel::Table(model) => model.pressure(state),
             AtmosphereModel::NoAtmosphere => 0.,
-        }
+        };
+        pressure * self.dispersion.pressure(state)
     }
 
-    /// Get the atmospheric density in kg/m^3.
+    /// Get the atmospheric density in kg/m^3, including the dispersion.
     ///
-    /// Uses the geopotential altitude of the state.
+    /// Uses the geopotential altitude or the altitude of the state, depending
+    /// on the model.
     pub fn density(&self, state: &State) -> f64 {
-        match self.model {
+        let density = match &self.model {
             AtmosphereModel::StandardAtmosphere1962 => {
                 standard_atmosphere_1962::density(state.altitude_geopotential)
             }
+            AtmosphereModel::StandardAtmosphere1976 => {
+                standard_atmosphere_1976::density(state.altitude)
+            }
+            AtmosphereModel::Exponential(model) => model.density(state.altitude),
+            AtmosphereModel::Mars => mars::density(state.altitude),
+            AtmosphereModel::Jacchia1971(model) => model.density(state, self.julian_date(state)),
+            AtmosphereModel::Table(model) => model.density(state),
             AtmosphereModel::NoAtmosphere => 0.,
-        }
+        };
+        density * self.dispersion.density(state)
     }
 
-    /// Get the speed of sound in m/s.
+    /// Get the speed of sound in m/s. It scales with the square root of the
+    /// dispersion of the temperature.
     ///
-    /// Uses the geopotential altitude of the state.
+    /// Uses the altitude of the state for the standard atmospheres, or the gas
+    /// properties and temperature of the model.
     fn speed_of_sound(&self, state: &State) -> f64 {
-        match self.model {
+        let speed_of_sound = match &self.model {
             AtmosphereModel::StandardAtmosphere1962 => {
                 standard_atmosphere_1962::speed_of_sound(state.altitude_geopotential)
             }
-            AtmosphereModel::NoAtmosphere => 0.,
-        }
+            AtmosphereModel::StandardAtmosphere1976 => {
+                standard_atmosphere_1976::speed_of_sound(state.altitude)
+            }
+            AtmosphereModel::Jacchia1971(model) => {
+                model.speed_of_sound(state, self.julian_date(state))
+            }
+            AtmosphereModel::Table(model) => model.speed_of_sound(state),
+            _ => self
+                .model
+                .gas_properties()
+                .speed_of_sound(self.model_temperature(state)),
+        };
+        speed_of_sound * self.dispersion.temperature(state).sqrt()
+    }
+
+    /// Get the Julian date (TT) of the state.
+    fn julian_date(&self, state: &State) -> f64 {
+        self.epoch + state.time / SECONDS_PER_DAY
     }
 
     /// Get the mach number.
@@ -114,6 +362,36 @@ impl Atmosphere {
         state.velocity_atmosphere.norm() / self.speed_of_sound(state)
     }
 
+    /// Get the dynamic viscosity in Pa s.
+    ///
+    /// Uses Sutherland's law for the gas of the model and the temperature of
+    /// the state.
+    pub fn viscosity(&self, state: &State) -> f64 {
+        self.model.gas_properties().viscosity(state.temperature)
+    }
+
+    /// Get the mean free path in m. Without an atmosphere, it is infinite.
+    ///
+    /// Uses the viscosity, temperature and pressure of the state.
+    pub fn mean_free_path(&self, state: &State) -> f64 {
+        self.model.gas_properties().mean_free_path(
+            state.viscosity,
+            state.temperature,
+            state.pressure,
+        )
+    }
+
+    /// Get the unit Reynolds number in 1/m.
+    ///
+    /// Uses the density, viscosity and velocity with respect to the atmosphere
+    /// of the state.
+    pub fn unit_reynolds_number(&self, state: &State) -> f64 {
+        if state.viscosity == 0. {
+            return 0.;
+        }
+        state.density * state.velocity_atmosphere.norm() / state.viscosity
+    }
+
     /// Get the dynamic pressure in Pa.
     ///
     /// Uses the density and velocity with respect to the atmosphere of the
@@ -122,12 +400,22 @@ impl Atmosphere {
         0.5 * state.density * state.velocity_atmosphere.norm().powi(2)
     }
 
-    /// Calculate the velocity with respect to the atmosphere in m/s.
+    /// Calculate the velocity with respect to the atmosphere in m/s in the
+    /// inertial frame.
     ///
     /// This is the the velocity with respect to the planet minus the static
-    /// wind vector.
-    pub fn velocity_atmosphere(&self, state: &State) -> Vector3<f64> {
-        state.velocity_planet - self.wind
+    /// wind vector, the wind of the profile and the gusts. The wind of the
+    /// profile and the gusts are rotated from the planet frame to the inertial
+    /// frame.
+    pub fn velocity_atmosphere(
+        &self,
+        state: &State,
+        inertial_to_planet: &Rotation3<f64>,
+    ) -> Vector3<f64> {
+        let wind_planet = self.wind_profile.wind_planet(state) + self.gusts.gust_planet(state);
+        state.velocity_planet
+            - self.wind
+            - inertial_to_planet.inverse_transform_vector(&wind_planet)
     }
 }
 
@@ -136,6 +424,7 @@ mod tests {
     use crate::assert_almost_eq_rel;
 
     use super::*;
+    use crate::config::PlanetConfig;
     use crate::example_data::DATA_POINTS;
 
     #[test]
@@ -171,4 +460,108 @@ mod tests {
             println!("ok");
         }
     }
+
+    #[test]
+    fn exponential() {
+        let mut atm = Atmosphere::default();
+        atm.update_with_planet(&Planet::update_with_config(&PlanetConfig::Mars));
+
+        // Only the atmosphere model of the planet is replaced
+        assert_eq!(atm.model, AtmosphereModel::NoAtmosphere);
+
+        atm.update_with_config(
+            &AtmosphereConfig {
+                model: Some(AtmosphereModelConfig::Planet),
+                wind: None,
+                wind_profile: None,
+                gusts: None,
+                dispersion: None,
+            },
+            &Planet::update_with_config(&PlanetConfig::Spherical),
+        );
+        assert_eq!(atm.model, AtmosphereModel::StandardAtmosphere1962);
+        atm.update_with_planet(&Planet::update_with_config(&PlanetConfig::Mars));
+        assert_eq!(atm.model, AtmosphereModel::Mars);
+        atm.update_with_planet(&Planet::update_with_config(&PlanetConfig::Venus));
+
+        let state = State {
+            altitude: 0.,
+            velocity_atmosphere: Vector3::new(CARBON_DIOXIDE.speed_of_sound(737.), 0., 0.),
+            ..Default::default()
+        };
+
+        // Venus surface density of about 65 kg/m^3
+        assert_almost_eq_rel!(atm.pressure(&state), 9.2e6_f64, 1e-9);
+        assert_almost_eq_rel!(atm.density(&state), 65_f64, 0.02);
+        assert_almost_eq_rel!(atm.mach_number(&state), 1_f64, 1e-9);
+
+        // The pressure drops by e over one scale height
+        let state = State {
+            altitude: 1.59e4,
+            ..Default::default()
+        };
+        assert_almost_eq_rel!(atm.pressure(&state), 9.2e6 / std::f64::consts::E, 1e-9);
+    }
+
+    #[test]
+    fn table() {
+        let config: AtmosphereConfig = serde_json::from_value(serde_json::json!({
+            "model": { "table": {
+                "temperature": { "x": ["altitude", [0, 1000]], "data": [290, 280] },
+                "pressure": { "x": ["altitude", [0, 1000]], "data": [1e5, 9e4] },
+                "density": { "x": ["altitude", [0, 1000]], "data": [1.2, 1.1] },
+                "speed_of_sound": { "x": ["altitude", [0, 1000]], "data": [340, 335] }
+            }}
+        }))
+        .unwrap();
+
+        let mut atm = Atmosphere::default();
+        atm.update_with_config(&config, &Planet::default());
+
+        let state = State {
+            altitude: 500.,
+            velocity_atmosphere: Vector3::new(337.5, 0., 0.),
+            ..Default::default()
+        };
+        assert_almost_eq_rel!(atm.temperature(&state), 285_f64, 1e-12);
+        assert_almost_eq_rel!(atm.pressure(&state), 9.5e4_f64, 1e-12);
+        assert_almost_eq_rel!(atm.density(&state), 1.15_f64, 1e-12);
+        assert_almost_eq_rel!(atm.mach_number(&state), 1_f64, 1e-12);
+
+        // The extrapolated values are limited to zero
+        let state = State {
+            altitude: 1e4,
+            ..Default::default()
+        };
+        assert_eq!(atm.pressure(&state), 0.);
+    }
+
+    #[test]
+    fn viscosity() {
+        let atm = Atmosphere {
+            model: AtmosphereModel::StandardAtmosphere1976,
+            ..Default::default()
+        };
+
+        // Sea level values of the 1976 U.S. Standard Atmosphere
+        let mut state = State {
+            velocity_atmosphere: Vector3::new(100., 0., 0.),
+            ..Default::default()
+        };
+        state.temperature = atm.temperature(&state);
+        state.pressure = atm.pressure(&state);
+        state.density = atm.density(&state);
+        state.viscosity = atm.viscosity(&state);
+        assert_almost_eq_rel!(state.viscosity, 1.7894e-5_f64, 1e-4);
+        assert_almost_eq_rel!(atm.unit_reynolds_number(&state), 6.846e6_f64, 1e-3);
+
+        // The standard uses a collision diameter instead of the viscosity, so
+        // the mean free path differs slightly
+        assert_almost_eq_rel!(atm.mean_free_path(&state), 6.6328e-8_f64, 0.05);
+
+        let atm = Atmosphere::default();
+        let state = State::default();
+        assert_eq!(atm.unit_reynolds_number(&state), 0.);
+        assert_eq!(atm.mean_free_path(&state), f64::INFINITY);
+    }
 }
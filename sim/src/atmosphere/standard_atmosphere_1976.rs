@@ -0,0 +1,319 @@
+//! Implements the U.S. Standard Atmosphere, 1976 up to 1000 km, according to
+//! [NOAA-S/T 76-1562](https://ntrs.nasa.gov/api/citations/19770009539/downloads/19770009539.pdf).
+//!
+//! Below 86 km, the atmosphere is defined with layers of linear molecular
+//! temperature over the geopotential altitude, like the 1962 model. Above
+//! 86 km, the kinetic temperature is defined over the geometric altitude
+//! [p. 10 f.], and the mean molecular weight decreases due to the diffusive
+//! separation of the species. The pressure is then integrated with the
+//! hydrostatic equation, using the mean molecular weight interpolated from the
+//! tables of the standard [p. 210 ff.]. The results agree with the tables
+//! within 1 %.
+//!
+//! All functions take the geometric altitude in m.
+
+use crate::utils::constants::*;
+use std::sync::OnceLock;
+
+/// The layers below 86 km. The values are the base geopotential altitude in
+/// m, pressure in Pa, molecular temperature in K and temperature gradient in
+/// K/m.
+const LAYERS: [(f64, f64, f64, f64); 8] = [
+    (0., 101325., 288.15, -6.5e-3),
+    (11000., 22632.06, 216.65, 0.),
+    (20000., 5474.889, 216.65, 1e-3),
+    (32000., 868.0187, 228.65, 2.8e-3),
+    (47000., 110.9063, 270.65, 0.),
+    (51000., 66.93887, 270.65, -2.8e-3),
+    (71000., 3.956420, 214.65, -2e-3),
+    (84852., 0.3733836, 186.946, 0.),
+];
+
+/// The ratio of the mean molecular weight to the sea-level value between 80
+/// and 86 km geometric altitude in steps of 500 m [p. 9].
+const MOLECULAR_WEIGHT_RATIO: [f64; 13] = [
+    1., 0.999996, 0.999988, 0.999969, 0.999938, 0.999904, 0.999864, 0.999822, 0.999778, 0.999731,
+    0.999681, 0.999633, 0.999579,
+];
+
+/// The mean molecular weight in kg/kmol above 86 km over the geometric
+/// altitude in km.
+const MOLECULAR_WEIGHT: [(f64, f64); 29] = [
+    (86., 28.9522),
+    (90., 28.91),
+    (95., 28.73),
+    (100., 28.40),
+    (110., 27.27),
+    (120., 26.20),
+    (130., 25.44),
+    (140., 24.75),
+    (150., 24.10),
+    (160., 23.49),
+    (170., 22.90),
+    (180., 22.34),
+    (190., 21.81),
+    (200., 21.30),
+    (250., 19.19),
+    (300., 17.73),
+    (400., 15.98),
+    (450., 15.25),
+    (500., 14.33),
+    (550., 13.09),
+    (600., 11.51),
+    (650., 9.72),
+    (700., 8.00),
+    (750., 6.58),
+    (800., 5.54),
+    (850., 4.85),
+    (900., 4.40),
+    (950., 4.12),
+    (1000., 3.94),
+];
+
+/// The geometric altitude in m above which the upper atmosphere is used.
+const UPPER_ALTITUDE: f64 = 86e3;
+
+/// The step size in m of the precomputed pressures above 86 km.
+const PRESSURE_STEP: f64 = 1e3;
+
+/// Calculates the atmospheric temperature in K at the geometric altitude
+/// `alt`.
+pub fn temperature(alt: f64) -> f64 {
+    if alt < UPPER_ALTITUDE {
+        let (base_altitude, _, base_temperature, base_temp_gradient, geopotential_alt) =
+            lower_layer(alt);
+        let molecular_temperature =
+            base_temperature + base_temp_gradient * (geopotential_alt - base_altitude);
+        molecular_temperature * molecular_weight_ratio(alt)
+    } else {
+        upper_temperature(alt)
+    }
+}
+
+/// Calculates the atmospheric pressure in Pa at the geometric altitude `alt`.
+///
+/// Below 86 km, the same equations as in the 1962 model are used [eq. 33a,
+/// 33b]. Above 86 km, the precomputed pressures are interpolated
+/// logarithmically.
+pub fn pressure(alt: f64) -> f64 {
+    if alt < UPPER_ALTITUDE {
+        let (base_altitude, base_pressure, base_temperature, base_temp_gradient, geopotential_alt) =
+            lower_layer(alt);
+        let gravity_ratio = STD_GRAVITY / AIR_GAS_CONSTANT;
+
+        if base_temp_gradient != 0. {
+            let temperature =
+                base_temperature + base_temp_gradient * (geopotential_alt - base_altitude);
+            base_pressure
+                * (base_temperature / temperature).powf(gravity_ratio / base_temp_gradient)
+        } else {
+            base_pressure
+                * f64::exp(-gravity_ratio * (geopotential_alt - base_altitude) / base_temperature)
+        }
+    } else {
+        let pressures = upper_pressures();
+
+        // Extrapolate above 1000 km with the last step
+        let idx = (((alt - UPPER_ALTITUDE) / PRESSURE_STEP) as usize).min(pressures.len() - 2);
+        let fraction = (alt - UPPER_ALTITUDE) / PRESSURE_STEP - idx as f64;
+
+        f64::exp(pressures[idx] + fraction * (pressures[idx + 1] - pressures[idx]))
+    }
+}
+
+/// Calculates the atmospheric density in kg/m^3 at the geometric altitude
+/// `alt`.
+///
+/// Uses `rho = M * P / (R* * T)` [eq. 42].
+pub fn density(alt: f64) -> f64 {
+    pressure(alt) / (temperature(alt) * gas_constant(alt))
+}
+
+/// Calculates the speed of sound in m/s at the geometric altitude `alt`.
+///
+/// Uses `C_s = (gamma * R* * T / M)^0.5` [eq. 50]. The standard defines the
+/// speed of sound only below 86 km, but the equation is used above as well.
+pub fn speed_of_sound(alt: f64) -> f64 {
+    f64::sqrt(AIR_KAPPA * gas_constant(alt) * temperature(alt))
+}
+
+/// Helper function to retrieve the layer below 86 km, together with the
+/// geopotential altitude.
+fn lower_layer(alt: f64) -> (f64, f64, f64, f64, f64) {
+    let geopotential_alt = US76_EARTH_RADIUS * alt / (US76_EARTH_RADIUS + alt);
+
+    let (base_altitude, base_pressure, base_temperature, base_temp_gradient) = *LAYERS
+        .iter()
+        .rev()
+        .find(|layer| layer.0 <= geopotential_alt)
+        .unwrap_or(&LAYERS[0]);
+
+    (
+        base_altitude,
+        base_pressure,
+        base_temperature,
+        base_temp_gradient,
+        geopotential_alt,
+    )
+}
+
+/// Calculates the ratio of the mean molecular weight to the sea-level value
+/// at the geometric altitude `alt`.
+fn molecular_weight_ratio(alt: f64) -> f64 {
+    if alt <= 80e3 {
+        return 1.;
+    }
+    if alt >= UPPER_ALTITUDE {
+        return molecular_weight(alt) / AIR_MOLECULAR_WEIGHT;
+    }
+
+    let position = (alt - 80e3) / 500.;
+    let idx = (position as usize).min(MOLECULAR_WEIGHT_RATIO.len() - 2);
+    let fraction = position - idx as f64;
+    MOLECULAR_WEIGHT_RATIO[idx]
+        + fraction * (MOLECULAR_WEIGHT_RATIO[idx + 1] - MOLECULAR_WEIGHT_RATIO[idx])
+}
+
+/// Calculates the specific gas constant in J/(kg K) at the geometric altitude
+/// `alt`.
+fn gas_constant(alt: f64) -> f64 {
+    AIR_GAS_CONSTANT / molecular_weight_ratio(alt)
+}
+
+/// Calculates the kinetic temperature in K above 86 km at the geometric
+/// altitude `alt` [eq. 25 ff.].
+fn upper_temperature(alt: f64) -> f64 {
+    let alt_km = alt / 1e3;
+
+    if alt_km < 91. {
+        186.8673
+    } else if alt_km < 110. {
+        // Elliptical segment
+        263.1905 - 76.3232 * f64::sqrt(1. - ((alt_km - 91.) / -19.9429).powi(2))
+    } else if alt_km < 120. {
+        240. + 12. * (alt_km - 110.)
+    } else {
+        // Exponential segment
+        let radius = US76_EARTH_RADIUS / 1e3;
+        let xi = (alt_km - 120.) * (radius + 120.) / (radius + alt_km);
+        1000. - 640. * f64::exp(-0.01875 * xi)
+    }
+}
+
+/// Calculates the mean molecular weight in kg/kmol above 86 km at the
+/// geometric altitude `alt`.
+///
+/// The table is interpolated with a cubic Hermite spline, with the slopes
+/// from the neighboring points. Above 1000 km, the last value is used.
+fn molecular_weight(alt: f64) -> f64 {
+    let alt_km = alt / 1e3;
+    let last = MOLECULAR_WEIGHT.len() - 1;
+    if alt_km >= MOLECULAR_WEIGHT[last].0 {
+        return MOLECULAR_WEIGHT[last].1;
+    }
+
+    let idx = MOLECULAR_WEIGHT
+        .partition_point(|(base, _)| *base <= alt_km)
+        .clamp(1, last)
+        - 1;
+    let slope = |i: usize| {
+        let (prev, next) = (i.saturating_sub(1), (i + 1).min(last));
+        (MOLECULAR_WEIGHT[next].1 - MOLECULAR_WEIGHT[prev].1)
+            / (MOLECULAR_WEIGHT[next].0 - MOLECULAR_WEIGHT[prev].0)
+    };
+
+    let (x0, y0) = MOLECULAR_WEIGHT[idx];
+    let (x1, y1) = MOLECULAR_WEIGHT[idx + 1];
+    let h = x1 - x0;
+    let t = (alt_km - x0) / h;
+
+    (2. * t.powi(3) - 3. * t.powi(2) + 1.) * y0
+        + (t.powi(3) - 2. * t.powi(2) + t) * h * slope(idx)
+        + (-2. * t.powi(3) + 3. * t.powi(2)) * y1
+        + (t.powi(3) - t.powi(2)) * h * slope(idx + 1)
+}
+
+/// Returns the logarithm of the pressure in Pa above 86 km in steps of
+/// [`PRESSURE_STEP`] up to 1000 km.
+///
+/// They are integrated once with the hydrostatic equation
+/// `dP / dZ = -g * M * P / (R* * T)` [eq. 31] using Simpson's rule.
+fn upper_pressures() -> &'static [f64] {
+    static PRESSURES: OnceLock<Vec<f64>> = OnceLock::new();
+
+    PRESSURES.get_or_init(|| {
+        const SUBSTEPS: usize = 20;
+        let derivative = |alt: f64| {
+            let gravity = STD_GRAVITY * (US76_EARTH_RADIUS / (US76_EARTH_RADIUS + alt)).powi(2);
+            -gravity * molecular_weight(alt) / (GAS_CONSTANT * upper_temperature(alt))
+        };
+
+        let steps = ((1000e3 - UPPER_ALTITUDE) / PRESSURE_STEP) as usize;
+        let h = PRESSURE_STEP / SUBSTEPS as f64;
+        let mut pressures = vec![pressure(UPPER_ALTITUDE - 1e-6).ln()];
+        let mut ln_pressure = pressures[0];
+        for i in 0..steps * SUBSTEPS {
+            let alt = UPPER_ALTITUDE + i as f64 * h;
+            ln_pressure +=
+                h / 6. * (derivative(alt) + 4. * derivative(alt + h / 2.) + derivative(alt + h));
+            if (i + 1) % SUBSTEPS == 0 {
+                pressures.push(ln_pressure);
+            }
+        }
+
+        pressures
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_almost_eq_rel;
+
+    #[test]
+    fn lower_atmosphere() {
+        // Values from the tables of the standard: geometric altitude in m,
+        // temperature in K, pressure in Pa, density in kg/m^3 and speed of
+        // sound in m/s
+        let data: [(f64, f64, f64, f64, f64); 6] = [
+            (0., 288.15, 101325., 1.2250, 340.29),
+            (11e3, 216.77, 22699.9, 0.36480, 295.15),
+            (25e3, 221.55, 2549.2, 4.0084e-2, 298.39),
+            (50e3, 270.65, 79.779, 1.0269e-3, 329.80),
+            (80e3, 198.64, 1.0524, 1.8458e-5, 282.54),
+            (86e3 - 1e-6, 186.87, 0.37338, 6.958e-6, 274.10),
+        ];
+
+        for (alt, temperature_, pressure_, density_, speed_of_sound_) in data {
+            assert_almost_eq_rel!(temperature(alt), temperature_, 1e-4);
+            assert_almost_eq_rel!(pressure(alt), pressure_, 1e-4);
+            assert_almost_eq_rel!(density(alt), density_, 1e-3);
+            assert_almost_eq_rel!(speed_of_sound(alt), speed_of_sound_, 1e-4);
+        }
+    }
+
+    #[test]
+    fn upper_atmosphere() {
+        // Values from the tables of the standard: geometric altitude in m,
+        // temperature in K and density in kg/m^3
+        let data: [(f64, f64, f64); 9] = [
+            (86e3, 186.87, 6.958e-6),
+            (90e3, 186.87, 3.416e-6),
+            (100e3, 195.08, 5.604e-7),
+            (120e3, 360.00, 2.222e-8),
+            (150e3, 634.39, 2.076e-9),
+            (200e3, 854.56, 2.541e-10),
+            (300e3, 976.01, 1.916e-11),
+            (500e3, 999.24, 5.215e-13),
+            (1000e3, 1000.0, 3.561e-15),
+        ];
+
+        for (alt, temperature_, density_) in data {
+            assert_almost_eq_rel!(temperature(alt), temperature_, 1e-4);
+            assert_almost_eq_rel!(density(alt), density_, 0.01);
+        }
+
+        // Pressure is continuous at 86 km
+        assert_almost_eq_rel!(pressure(86e3 - 1e-3), pressure(86e3), 1e-6);
+    }
+}
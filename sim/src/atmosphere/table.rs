@@ -0,0 +1,60 @@
+//! Implements a user-defined atmosphere, given by tables. This allows to use
+//! measured data, e.g. soundings of the launch day.
+//!
+//! The tables are usually given against the `"altitude"` or the
+//! `"altitude_geopotential"`. Outside of the bases, the tables are
+//! extrapolated linearly, but the values are limited to zero.
+//!
+//! Without a table of the speed of sound, it is calculated from the
+//! temperature and the gas properties, which default to air.
+
+use super::GasProperties;
+use crate::state::State;
+use crate::utils::Table1D;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Represents an atmosphere given by tables.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TableAtmosphere {
+    /// Table of the temperature in K.
+    pub temperature: Table1D,
+    /// Table of the pressure in Pa.
+    pub pressure: Table1D,
+    /// Table of the density in kg/m^3.
+    pub density: Table1D,
+    /// Table of the speed of sound in m/s. Calculated from the temperature
+    /// if it is not given.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed_of_sound: Option<Table1D>,
+    /// Properties of the atmospheric gas. Defaults to air.
+    #[serde(default)]
+    pub gas: GasProperties,
+}
+
+impl TableAtmosphere {
+    /// Interpolates the temperature in K.
+    pub fn temperature(&self, state: &State) -> f64 {
+        self.temperature.at_state(state).max(0.)
+    }
+
+    /// Interpolates the pressure in Pa.
+    pub fn pressure(&self, state: &State) -> f64 {
+        self.pressure.at_state(state).max(0.)
+    }
+
+    /// Interpolates the density in kg/m^3.
+    pub fn density(&self, state: &State) -> f64 {
+        self.density.at_state(state).max(0.)
+    }
+
+    /// Interpolates the speed of sound in m/s, or calculates it from the
+    /// temperature and the gas properties.
+    pub fn speed_of_sound(&self, state: &State) -> f64 {
+        match &self.speed_of_sound {
+            Some(table) => table.at_state(state).max(0.),
+            None => self.gas.speed_of_sound(self.temperature(state)),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Implements the [`WindProfile`], which defines the wind relative to the
+//! planet surface, e.g. measured by soundings of the launch day.
+//!
+//! The wind components are given by tables in the local north-east-up frame,
+//! which is defined by the geodetic latitude and the longitude. The tables are
+//! usually given against the `"altitude"`.
+
+use crate::state::State;
+use crate::utils::Table1D;
+use nalgebra::{vector, Vector3};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Represents the wind components over the altitude. Missing components are
+/// zero.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct WindProfile {
+    /// Table of the wind blowing towards the north in m/s.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub north: Option<Table1D>,
+    /// Table of the wind blowing towards the east in m/s.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub east: Option<Table1D>,
+    /// Table of the upward wind in m/s.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub up: Option<Table1D>,
+}
+
+impl WindProfile {
+    /// Calculates the wind vector in m/s in the planet frame.
+    ///
+    /// Uses the geodetic latitude and the longitude of the state to rotate the
+    /// wind from the north-east-up frame into the planet frame.
+    pub fn wind_planet(&self, state: &State) -> Vector3<f64> {
+        let component = |table: &Option<Table1D>| match table {
+            Some(table) => table.at_state(state),
+            None => 0.,
+        };
+        let (north, east, up) = (
+            component(&self.north),
+            component(&self.east),
+            component(&self.up),
+        );
+        if north == 0. && east == 0. && up == 0. {
+            return Vector3::zeros();
+        }
+
+        north_east_up_to_planet(state, north, east, up)
+    }
+}
+
+/// Rotates a vector from the local north-east-up frame into the planet frame,
+/// using the geodetic latitude and the longitude of the state.
+pub(super) fn north_east_up_to_planet(
+    state: &State,
+    north: f64,
+    east: f64,
+    up: f64,
+) -> Vector3<f64> {
+    let (sin_lat, cos_lat) = state.latitude_geodetic.sin_cos();
+    let (sin_long, cos_long) = state.longitude.sin_cos();
+
+    let north_axis = vector![-sin_lat * cos_long, -sin_lat * sin_long, cos_lat];
+    let east_axis = vector![-sin_long, cos_long, 0.];
+    let up_axis = vector![cos_lat * cos_long, cos_lat * sin_long, sin_lat];
+
+    north * north_axis + east * east_axis + up * up_axis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_almost_eq_rel;
+
+    #[test]
+    fn wind_planet() {
+        let profile: WindProfile = serde_json::from_value(serde_json::json!({
+            "north": { "x": ["altitude", [0, 1e4]], "data": [0, 20] },
+            "east": { "x": ["altitude", [0, 1e4]], "data": [10, 30] }
+        }))
+        .unwrap();
+
+        // At the equator and the prime meridian, east is y and north is z
+        let state = State {
+            altitude: 5e3,
+            ..Default::default()
+        };
+        assert_almost_eq_rel!(vec profile.wind_planet(&state), vector![0_f64, 20., 10.], 1e-12);
+
+        // At the north pole and 90° longitude, north points to -y
+        let state = State {
+            altitude: 1e4,
+            latitude_geodetic: 90_f64.to_radians(),
+            longitude: 90_f64.to_radians(),
+            ..Default::default()
+        };
+        let wind = profile.wind_planet(&state);
+        assert_almost_eq_rel!(wind.y, -20_f64, 1e-12);
+        assert_almost_eq_rel!(wind.x, -30_f64, 1e-12);
+        assert!(wind.z.abs() < 1e-12);
+
+        assert_eq!(WindProfile::default().wind_planet(&state), Vector3::zeros());
+    }
+}
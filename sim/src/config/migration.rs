@@ -0,0 +1,262 @@
+//! Handles the `"version"` of the configuration file.
+//!
+//! Each change of the configuration format, which would break existing
+//! configuration files, increases [`VERSION`] and adds a migration to
+//! [`MIGRATIONS`]. Older configuration files are then migrated step by step
+//! to the current version, before they are deserialized.
+//!
+//! Files without a `"version"` (including the plain array of phase
+//! configurations) are of version 1.
+
+use super::parameters;
+use serde_json::{Map, Value};
+use std::error::Error;
+use std::fmt::Display;
+
+/// Migrates the content of a configuration file by one version. The content
+/// is always in the object form, i.e. with the `"phases"` array.
+type Migration = fn(&mut Map<String, Value>);
+
+/// All migrations. The first entry migrates from version 1 to version 2, and
+/// so on.
+const MIGRATIONS: &[Migration] = &[atmosphere_model];
+
+/// The current version of the configuration format.
+pub const VERSION: u64 = MIGRATIONS.len() as u64 + 1;
+
+/// Version 2: Replaces the atmosphere's `"enabled"` flag with the named
+/// `"model"`. Enabled atmospheres use the atmosphere model of the planet.
+/// Parameterized flags are resolved, as a boolean parameter can not be used
+/// as the model.
+fn atmosphere_model(file: &mut Map<String, Value>) {
+    let parameters = file
+        .get("parameters")
+        .and_then(Value::as_object)
+        .cloned()
+        .unwrap_or_default();
+    let Some(phases) = file.get_mut("phases").and_then(Value::as_array_mut) else {
+        return;
+    };
+
+    for atmosphere in phases
+        .iter_mut()
+        .filter_map(|phase| phase.get_mut("atmosphere"))
+        .filter_map(Value::as_object_mut)
+    {
+        if let Some(enabled) = atmosphere.remove("enabled") {
+            let mut resolved = enabled.clone();
+            let model = match parameters::resolve(&parameters, &mut resolved).map(|_| resolved) {
+                Ok(Value::Bool(true)) => "planet".into(),
+                Ok(Value::Bool(false)) => "none".into(),
+                // Keep invalid values for the error message
+                _ => enabled,
+            };
+            atmosphere.insert("model".into(), model);
+        }
+    }
+}
+
+/// Represents an error while migrating the configuration file.
+#[derive(Debug, PartialEq)]
+pub enum MigrationError {
+    /// The version is not a positive integer.
+    InvalidVersion(Value),
+    /// The version is newer than the current version.
+    UnknownVersion(u64),
+    /// The content is neither an array nor an object.
+    InvalidFormat,
+}
+
+impl Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationError::InvalidVersion(version) => {
+                write!(f, "Invalid configuration version {version}")
+            }
+            MigrationError::UnknownVersion(version) => write!(
+                f,
+                "Configuration version {version} is newer than the supported version {VERSION}"
+            ),
+            MigrationError::InvalidFormat => write!(
+                f,
+                "The configuration must be an array of phases or an object with \"phases\""
+            ),
+        }
+    }
+}
+
+impl Error for MigrationError {}
+
+/// Represents the content of a configuration file after the migration.
+#[derive(Debug)]
+pub struct Migrated {
+    /// The migrated content. If the content was migrated, it is in the object
+    /// form with the current `"version"`.
+    pub value: Value,
+    /// The version of the original content.
+    pub version: u64,
+}
+
+/// Migrates the content of a configuration file to the current version.
+pub fn migrate(value: Value) -> Result<Migrated, MigrationError> {
+    migrate_with(value, MIGRATIONS)
+}
+
+/// Migrates the content with the passed migrations. The target version is
+/// the number of migrations plus 1.
+fn migrate_with(value: Value, migrations: &[Migration]) -> Result<Migrated, MigrationError> {
+    let target = migrations.len() as u64 + 1;
+
+    let (mut file, version) = match value {
+        Value::Array(phases) => {
+            if target == 1 {
+                // Nothing to do, keep the plain array
+                return Ok(Migrated {
+                    value: Value::Array(phases),
+                    version: 1,
+                });
+            }
+            (Map::from_iter([("phases".into(), Value::Array(phases))]), 1)
+        }
+        Value::Object(file) => {
+            let version = match file.get("version") {
+                None => 1,
+                Some(version) => version
+                    .as_u64()
+                    .filter(|version| *version >= 1)
+                    .ok_or_else(|| MigrationError::InvalidVersion(version.clone()))?,
+            };
+            (file, version)
+        }
+        _ => return Err(MigrationError::InvalidFormat),
+    };
+
+    if version > target {
+        return Err(MigrationError::UnknownVersion(version));
+    }
+
+    for migration in migrations[version as usize - 1..].iter() {
+        migration(&mut file);
+    }
+    if version < target {
+        file.insert("version".into(), target.into());
+    }
+
+    Ok(Migrated {
+        value: Value::Object(file),
+        version,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Renames `"stepsize"` to `"step"` in each phase.
+    fn rename_stepsize(file: &mut Map<String, Value>) {
+        for phase in file["phases"].as_array_mut().unwrap() {
+            if let Some(stepsize) = phase.as_object_mut().unwrap().remove("stepsize") {
+                phase["step"] = stepsize;
+            }
+        }
+    }
+
+    /// Doubles each `"step"`.
+    fn double_step(file: &mut Map<String, Value>) {
+        for phase in file["phases"].as_array_mut().unwrap() {
+            if let Some(step) = phase.get_mut("step") {
+                *step = json!(step.as_f64().unwrap() * 2.);
+            }
+        }
+    }
+
+    #[test]
+    fn current_version() {
+        let value = json!({ "version": VERSION, "phases": [{ "stepsize": 5 }] });
+
+        let migrated = migrate(value.clone()).unwrap();
+
+        assert_eq!(migrated.version, VERSION);
+        assert_eq!(migrated.value, value);
+    }
+
+    #[test]
+    fn version_2() {
+        let value = json!([
+            { "atmosphere": { "enabled": true, "wind": [1, 0, 0] } },
+            { "atmosphere": { "enabled": false } },
+            { "stepsize": 5 }
+        ]);
+
+        let migrated = migrate_with(value, &MIGRATIONS[..1]).unwrap();
+        assert_eq!(migrated.version, 1);
+        assert_eq!(
+            migrated.value,
+            json!({
+                "version": 2,
+                "phases": [
+                    { "atmosphere": { "model": "planet", "wind": [1, 0, 0] } },
+                    { "atmosphere": { "model": "none" } },
+                    { "stepsize": 5 }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn version_2_parameters() {
+        let value = json!({
+            "parameters": { "atm": true, "wind": 1 },
+            "phases": [
+                { "atmosphere": { "enabled": "${atm}", "wind": ["${wind}", 0, 0] } },
+                { "atmosphere": { "enabled": "${unknown}" } }
+            ]
+        });
+
+        let migrated = migrate_with(value, &MIGRATIONS[..1]).unwrap();
+        assert_eq!(
+            migrated.value,
+            json!({
+                "version": 2,
+                "parameters": { "atm": true, "wind": 1 },
+                "phases": [
+                    { "atmosphere": { "model": "planet", "wind": ["${wind}", 0, 0] } },
+                    { "atmosphere": { "model": "${unknown}" } }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn migrate_steps() {
+        let migrations: &[Migration] = &[rename_stepsize, double_step];
+
+        let migrated = migrate_with(json!([{ "stepsize": 5. }]), migrations).unwrap();
+        assert_eq!(migrated.version, 1);
+        assert_eq!(
+            migrated.value,
+            json!({ "phases": [{ "step": 10. }], "version": 3 })
+        );
+
+        let value = json!({ "version": 2, "phases": [{ "step": 5. }] });
+        let migrated = migrate_with(value, migrations).unwrap();
+        assert_eq!(migrated.version, 2);
+        assert_eq!(
+            migrated.value,
+            json!({ "version": 3, "phases": [{ "step": 10. }] })
+        );
+    }
+
+    #[test]
+    fn invalid_version() {
+        assert_eq!(
+            migrate(json!({ "version": VERSION + 1, "phases": [] })).unwrap_err(),
+            MigrationError::UnknownVersion(VERSION + 1)
+        );
+        assert_eq!(
+            migrate(json!({ "version": "1", "phases": [] })).unwrap_err(),
+            MigrationError::InvalidVersion(json!("1"))
+        );
+    }
+}
@@ -0,0 +1,365 @@
+// Created by Tibor Völcker (tiborvoelcker@hotmail.de) on 21.04.24
+// Last modified by Tibor Völcker on 29.05.24
+// Copyright (c) 2024 Tibor Völcker (tiborvoelcker@hotmail.de)
+
+//! Defines the [`PhaseConfig`] which can deserialize the configuration file.
+//! It holds the configuration for each phase and can be used to build it.
+//!
+//! Most fields are declared as `Option`s, as the values of the previous phase
+//! are used if they are not declared.
+//!
+//! The configuration file is either an array of phase configurations, or an
+//! object with the array in `"phases"` and named values in `"parameters"`,
+//! which can be referenced in the phases (see [`parameters`]). The object
+//! can also declare the `"version"` of the format, so older files can be
+//! migrated to the current format (see [`migration`]). The phases are
+//! validated together with the inherited configuration (see [`validation`]).
+
+mod migration;
+mod parameters;
+mod schema;
+mod validation;
+
+use crate::atmosphere::{AtmosphereDispersion, Gusts, Jacchia1971, TableAtmosphere, WindProfile};
+use crate::epoch::Epoch;
+use crate::orientation::PlanetOrientation;
+use crate::planet::{GravityField, Terrain};
+use crate::state::StateVariable;
+use crate::third_body::ThirdBody;
+use crate::utils::Table;
+use crate::vehicle::{Burnout, Engine, EngineState, Stage, Throttle, ThrottleLimits};
+use nalgebra::Vector3;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+pub use migration::{migrate, VERSION};
+pub use schema::schema;
+
+/// Represents the content of a configuration file.
+///
+/// The phases are kept as raw values, as they can only be deserialized after
+/// the parameters are resolved.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(untagged)]
+#[serde(deny_unknown_fields)]
+enum ConfigFile {
+    /// An array of phase configurations.
+    Phases(#[schemars(with = "Vec<PhaseConfig>")] Vec<Value>),
+    /// An array of phase configurations with parameters.
+    WithParameters {
+        /// Version of the configuration format. Defaults to 1.
+        // Only read by `migrate`, but declared for the schema.
+        #[serde(default)]
+        #[allow(dead_code)]
+        version: Option<u64>,
+        /// Named values which can be referenced in the phases with
+        /// `"${name}"`, also as part of arithmetic expressions.
+        #[serde(default)]
+        parameters: Map<String, Value>,
+        /// An array of phase configurations.
+        #[schemars(with = "Vec<PhaseConfig>")]
+        phases: Value,
+    },
+}
+
+/// Builds the phase configurations from the content of a configuration file.
+///
+/// Files of an older version are migrated with [`migrate`] first, which
+/// prints a warning. If the file defines parameters, all references to them
+/// are resolved with [`parameters::resolve`] before the phases are
+/// deserialized. Finally, the phases are validated with
+/// [`validation::validate`].
+pub fn from_value(value: Value) -> Result<Vec<PhaseConfig>, Box<dyn Error>> {
+    let migrated = migrate(value)?;
+    if migrated.version < VERSION {
+        eprintln!(
+            "Warning: Configuration version {} is outdated and was migrated to version \
+            {VERSION}. Use the \"migrate\" command to update the file.",
+            migrated.version
+        );
+    }
+
+    let phases = match serde_json::from_value(migrated.value)? {
+        ConfigFile::Phases(phases) => Value::Array(phases),
+        ConfigFile::WithParameters {
+            parameters,
+            mut phases,
+            ..
+        } => {
+            parameters::resolve(&parameters, &mut phases)?;
+            phases
+        }
+    };
+
+    let phases: Vec<PhaseConfig> = serde_json::from_value(phases)?;
+    validation::validate(&phases)?;
+
+    Ok(phases)
+}
+
+/// Configurations regarding the [`crate::planet::Planet`].
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanetConfig {
+    /// Spherical earth model
+    /// See [`crate::planet`] for more information.
+    #[default]
+    Spherical,
+    /// 1960 Fisher earth model (includes up to J2)
+    /// See [`crate::planet`] for more information.
+    Fisher1960,
+    /// Smithsonian earth model (includes up to J4)
+    /// See [`crate::planet`] for more information.
+    Smithsonian,
+    /// Moon model (includes up to J2, no atmosphere)
+    /// See [`crate::planet`] for more information.
+    Moon,
+    /// Mars model (includes up to J2, Mars atmosphere model)
+    /// See [`crate::planet`] for more information.
+    Mars,
+    /// Venus model (includes up to J2, exponential CO2 atmosphere)
+    /// See [`crate::planet`] for more information.
+    Venus,
+    /// Use a custom implementation. The atmosphere is the 1962 U.S. Standard
+    /// Atmosphere.
+    /// See [`crate::planet`] for more information.
+    Custom {
+        /// Equatorial radius in m.
+        equatorial_radius: f64,
+        /// Polar radius in m.
+        polar_radius: f64,
+        /// Gravitational constant in m^3/s^2, followed by the gravitational
+        /// harmonics J2 to J4.
+        gravitational_parameters: [f64; 4],
+        /// Rotation rate in rad/s.
+        rotation_rate: f64,
+    },
+    /// Spherical harmonic gravity model of arbitrary degree and order.
+    /// See [`crate::planet`] for more information.
+    SphericalHarmonics {
+        /// The normalized coefficients, read from a coefficient file.
+        gravity_field: GravityField,
+        /// Equatorial radius in m. Defaults to the Smithsonian earth model.
+        equatorial_radius: Option<f64>,
+        /// Polar radius in m. Defaults to the Smithsonian earth model.
+        polar_radius: Option<f64>,
+        /// Rotation rate in rad/s. Defaults to the Smithsonian earth model.
+        rotation_rate: Option<f64>,
+    },
+}
+
+/// Configurations regarding the [`crate::atmosphere::Atmosphere`].
+/// The fields are `Option`s, as the values of the previous phase are used if
+/// they are not declared.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AtmosphereConfig {
+    /// The atmosphere model.
+    pub model: Option<AtmosphereModelConfig>,
+    /// Static wind vector in inertial frame in m/s.
+    #[schemars(with = "Option<[f64; 3]>")]
+    pub wind: Option<Vector3<f64>>,
+    /// Wind relative to the planet surface, given by tables of the north, east
+    /// and up components. It is added to the static wind. To disable it, set
+    /// it to `{}`.
+    pub wind_profile: Option<WindProfile>,
+    /// Turbulent gusts with the Dryden or von Kármán spectrum, added to the
+    /// wind. To disable them, set them to `{}`.
+    pub gusts: Option<Gusts>,
+    /// Dispersions of the temperature, pressure and density, e.g. for studies
+    /// of the atmospheric uncertainty. To disable them, set them to `{}`.
+    pub dispersion: Option<AtmosphereDispersion>,
+}
+
+/// Configurations regarding the [`crate::atmosphere::AtmosphereModel`].
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AtmosphereModelConfig {
+    /// No atmosphere.
+    None,
+    /// The atmosphere model of the planet, e.g. the 1962 U.S. Standard
+    /// Atmosphere for the earth models. It changes with the planet.
+    Planet,
+    /// The 1962 U.S. Standard Atmosphere.
+    #[serde(rename = "standard_atmosphere_1962")]
+    StandardAtmosphere1962,
+    /// The 1976 U.S. Standard Atmosphere up to 1000 km.
+    #[serde(rename = "standard_atmosphere_1976")]
+    StandardAtmosphere1976,
+    /// The Mars atmosphere model, a fit of measurements of the Mars Global
+    /// Surveyor with carbon dioxide as gas.
+    /// See [`crate::atmosphere`] for more information.
+    Mars,
+    /// The Jacchia 1971 thermosphere model, which depends on the solar and
+    /// geomagnetic activity and the position relative to the Sun. Only valid
+    /// for the Earth.
+    /// See [`crate::atmosphere::Jacchia1971`] for more information.
+    #[serde(rename = "jacchia_1971")]
+    Jacchia1971(Jacchia1971),
+    /// An atmosphere given by tables, e.g. from measured soundings.
+    /// See [`crate::atmosphere::TableAtmosphere`] for more information.
+    Table(TableAtmosphere),
+}
+
+/// Configurations regarding the initialization.
+/// This will define the starting position and velocity of the vehicle, as well
+/// as the launch frame.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct InitConfig {
+    /// Geodetic latitude in °.
+    pub latitude: f64,
+    /// Longitude in °.
+    pub longitude: f64,
+    /// Orientation of the launch frame in ° (changes pitch direction).
+    pub azimuth: f64,
+    /// Geodetic altitude above the surface in m to initialize the vehicle.
+    /// The launch frame is not affected.
+    pub altitude: f64,
+    /// Epoch at the start of the simulation, either as Julian date (TT) or as
+    /// calendar date. Used for the orientation of the planet and the positions
+    /// of the Sun and the Moon. Defaults to J2000.
+    pub epoch: Option<Epoch>,
+    /// Model of the orientation of the planet frame relative to the inertial
+    /// frame (mean equator and equinox of J2000). Defaults to `sidereal_time`
+    /// if an epoch is given, otherwise to `aligned`. Only `aligned` is valid
+    /// for planets other than the Earth.
+    pub orientation: Option<PlanetOrientation>,
+}
+
+/// Configurations regarding the [`crate::vehicle::Vehicle`].
+/// The fields are `Option`s, as the values of the previous phase are used if
+/// they are not declared.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct VehicleConfig {
+    /// Mass of the vehicle without propellant in kg.
+    pub structure_mass: Option<f64>,
+    /// Initial mass of the propellant in kg.
+    pub propellant_mass: Option<f64>,
+    /// Reference area of the vehicle in m^2. Used for aerodynamic calculations.
+    pub reference_area: Option<f64>,
+    /// Reference length of the vehicle in m. Used for the Reynolds and
+    /// Knudsen number.
+    pub reference_length: Option<f64>,
+    /// Table used to calculate the drag coefficients.
+    pub drag_coeff: Option<Table>,
+    /// Table used to calculate the lift coefficients.
+    pub lift_coeff: Option<Table>,
+    /// Table used to calculate the side-force coefficients.
+    pub side_force_coeff: Option<Table>,
+    /// Engines of the vehicle.
+    /// Either all engines or no engines can be changed. To disable thrust,
+    /// remove the engines by setting them to `[]`. Single engines can be
+    /// changed with `engine_states`.
+    pub engines: Option<Vec<Engine>>,
+    /// Changes of the ignition and throttle of single engines by their name,
+    /// e.g. `{ "center": { "ignited": false } }` for an engine failure. The
+    /// engines of the remaining stages keep their state until they are active.
+    pub engine_states: Option<BTreeMap<String, EngineState>>,
+    /// Stages of the vehicle from the bottom to the top. They replace the
+    /// structure mass, propellant mass and engines, and the aerodynamics if
+    /// given. A stage is separated automatically when its propellant is
+    /// depleted. To disable staging, set them to `[]`.
+    pub stages: Option<Vec<Stage>>,
+    /// Whether to separate the active stage at the start of the phase.
+    pub separate_stage: Option<bool>,
+    /// Behavior when the propellant is depleted. Defaults to `"cutoff"`.
+    pub burnout: Option<Burnout>,
+}
+
+/// Configuration regarding the [`crate::steering::Steering`].
+///
+/// The orientation is calculated with cubic polynomials using 4 coefficients
+/// and a chosen state variables.
+/// The coefficients are stored in ascending order: c1*y + c2*y^2 + c3*y^3
+///
+/// The fields are `Option`s, as the values of the previous phase are used if
+/// they are not declared.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SteeringConfig {
+    /// State variable (unit X) and coefficients in °/X for the roll axis.
+    pub roll: Option<(StateVariable, [f64; 3])>,
+    /// State variable (unit X) and coefficients in °/X for the yaw axis.
+    pub yaw: Option<(StateVariable, [f64; 3])>,
+    /// State variable (unit X) and coefficients in °/X for the pitch axis.
+    pub pitch: Option<(StateVariable, [f64; 3])>,
+}
+
+/// Configuration of the [`crate::phase::Phase`].
+/// The fields are `Option`s, as the values of the previous phase are used if
+/// they are not declared.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PhaseConfig {
+    /// Name of the phase, used in the output. Defaults to the phase number.
+    pub name: Option<String>,
+    /// Initialization configuration.
+    pub init: Option<InitConfig>,
+    /// Vehicle configuration.
+    pub vehicle: Option<VehicleConfig>,
+    /// Steering configuration.
+    pub steering: Option<SteeringConfig>,
+    /// Planet configuration.
+    pub planet_model: Option<PlanetConfig>,
+    /// Atmosphere configuration.
+    pub atmosphere: Option<AtmosphereConfig>,
+    /// Celestial bodies whose gravity perturbs the trajectory. Only supported
+    /// for the earth models with an orientation tied to J2000 (not `aligned`),
+    /// as the positions are geocentric in J2000. To disable the
+    /// perturbations, set them to `[]`.
+    pub third_bodies: Option<Vec<ThirdBody>>,
+    /// Terrain elevation, used for the altitude above ground.
+    pub terrain: Option<Terrain>,
+    /// Maximum allowed acceleration in m/s^2.
+    pub max_acceleration: Option<f64>,
+    /// Commanded throttle as polynomial or table of state variables, e.g.
+    /// `["time", [1]]` for full throttle. The throttle is limited to stay
+    /// within the maximum acceleration.
+    pub throttle: Option<Throttle>,
+    /// Limits of the throttle by the dynamic pressure, the axial acceleration
+    /// and the thrust-to-weight ratio. To disable them, set them to `{}`.
+    pub throttle_limits: Option<ThrottleLimits>,
+    /// Default integrator step size in sec.
+    pub stepsize: Option<f64>,
+    /// The variable and its target value to end the phase.
+    pub end_criterion: Option<(StateVariable, f64)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_example() {
+        let str = include_str!("../../../utils/example.json");
+
+        from_value(serde_json::from_str(str).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn deserialize_with_parameters() {
+        let value = serde_json::json!({
+            "parameters": { "target_alt": 9.0e4, "rate": -0.4 },
+            "phases": [
+                {
+                    "steering": { "pitch": [ "time_since_event", [ "${rate}", 0, 0 ] ] },
+                    "end_criterion": [ "altitude", "${target_alt} + 1000" ]
+                }
+            ]
+        });
+
+        let config = from_value(value).unwrap();
+
+        assert_eq!(
+            config[0].steering.as_ref().unwrap().pitch.unwrap().1[0],
+            -0.4
+        );
+        assert_eq!(config[0].end_criterion.unwrap().1, 91000.);
+    }
+}
@@ -0,0 +1,356 @@
+//! Handles the `"parameters"` block of the configuration file.
+//!
+//! Parameters are named values which can be referenced anywhere in the phase
+//! configurations with `"${name}"`. A string which consists of exactly one
+//! reference is replaced by the parameter value, whatever its type. Any other
+//! string containing a reference is evaluated as an arithmetic expression,
+//! e.g. `"${target_alt} + 1000"`, and replaced by the resulting number.
+//!
+//! Expressions support numbers, references, parentheses, the unary minus and
+//! the operators `+`, `-`, `*`, `/` and `^`. Parameters can reference other
+//! parameters, as long as the references are not circular.
+
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+
+/// Represents an error while resolving the parameters.
+#[derive(Debug, PartialEq)]
+pub enum ParameterError {
+    /// A parameter was referenced, but never defined.
+    UnknownParameter(String),
+    /// A parameter references itself (directly or over other parameters).
+    CircularReference(String),
+    /// A parameter was used in an arithmetic expression, but is not a number.
+    NotANumber(String),
+    /// The expression could not be parsed or evaluated.
+    InvalidExpression(String, String),
+}
+
+impl Display for ParameterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParameterError::UnknownParameter(name) => {
+                write!(f, "Parameter \"{name}\" is not defined")
+            }
+            ParameterError::CircularReference(name) => {
+                write!(f, "Parameter \"{name}\" references itself")
+            }
+            ParameterError::NotANumber(name) => {
+                write!(
+                    f,
+                    "Parameter \"{name}\" is used in an expression, but is not a number"
+                )
+            }
+            ParameterError::InvalidExpression(expr, reason) => {
+                write!(f, "Invalid expression \"{expr}\": {reason}")
+            }
+        }
+    }
+}
+
+impl Error for ParameterError {}
+
+/// Replaces all references and expressions in `value` with the values of
+/// `parameters`.
+pub fn resolve(parameters: &Map<String, Value>, value: &mut Value) -> Result<(), ParameterError> {
+    let mut resolver = Resolver {
+        parameters,
+        resolved: HashMap::new(),
+        stack: Vec::new(),
+    };
+    resolver.resolve_value(value)
+}
+
+/// Helper struct to resolve the parameters lazily. Each parameter is resolved
+/// once, when it is referenced the first time.
+struct Resolver<'a> {
+    /// The unresolved parameters as defined in the configuration file.
+    parameters: &'a Map<String, Value>,
+    /// The parameters which were already resolved.
+    resolved: HashMap<String, Value>,
+    /// The parameters which are currently being resolved. Used to detect
+    /// circular references.
+    stack: Vec<String>,
+}
+
+impl Resolver<'_> {
+    /// Recursively resolves all strings in `value`.
+    fn resolve_value(&mut self, value: &mut Value) -> Result<(), ParameterError> {
+        match value {
+            Value::String(str) if str.contains("${") => {
+                *value = self.resolve_string(str)?;
+            }
+            Value::Array(arr) => {
+                for value in arr {
+                    self.resolve_value(value)?;
+                }
+            }
+            Value::Object(obj) => {
+                for value in obj.values_mut() {
+                    self.resolve_value(value)?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Resolves a string, which is either a single reference or an
+    /// expression.
+    fn resolve_string(&mut self, str: &str) -> Result<Value, ParameterError> {
+        let trimmed = str.trim();
+        if let Some(name) = trimmed
+            .strip_prefix("${")
+            .and_then(|s| s.strip_suffix('}'))
+            .filter(|name| is_valid_name(name))
+        {
+            return self.get(name);
+        }
+
+        let result = Parser::new(str, self).parse()?;
+        Number::from_f64(result).map(Value::Number).ok_or_else(|| {
+            ParameterError::InvalidExpression(str.into(), "result is not finite".into())
+        })
+    }
+
+    /// Returns the resolved value of a parameter.
+    fn get(&mut self, name: &str) -> Result<Value, ParameterError> {
+        if let Some(value) = self.resolved.get(name) {
+            return Ok(value.clone());
+        }
+        if self.stack.iter().any(|i| i == name) {
+            return Err(ParameterError::CircularReference(name.into()));
+        }
+
+        let mut value = self
+            .parameters
+            .get(name)
+            .ok_or_else(|| ParameterError::UnknownParameter(name.into()))?
+            .clone();
+
+        self.stack.push(name.into());
+        self.resolve_value(&mut value)?;
+        self.stack.pop();
+
+        self.resolved.insert(name.into(), value.clone());
+        Ok(value)
+    }
+
+    /// Returns the resolved value of a parameter as number.
+    fn get_number(&mut self, name: &str) -> Result<f64, ParameterError> {
+        self.get(name)?
+            .as_f64()
+            .ok_or_else(|| ParameterError::NotANumber(name.into()))
+    }
+}
+
+/// Checks whether the parameter name only consists of alphanumeric characters
+/// and underscores.
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A small recursive descent parser for arithmetic expressions.
+///
+/// The grammar is:
+/// ```text
+/// expr    = term (("+" | "-") term)*
+/// term    = power (("*" | "/") power)*
+/// power   = unary ("^" power)?
+/// unary   = "-" unary | primary
+/// primary = number | "${" name "}" | "(" expr ")"
+/// ```
+struct Parser<'e, 'r, 'p> {
+    /// The complete expression, used for error messages.
+    expr: &'e str,
+    /// The remaining part of the expression.
+    rest: &'e str,
+    /// The resolver used to look up references.
+    resolver: &'r mut Resolver<'p>,
+}
+
+impl<'e, 'r, 'p> Parser<'e, 'r, 'p> {
+    fn new(expr: &'e str, resolver: &'r mut Resolver<'p>) -> Self {
+        Self {
+            expr,
+            rest: expr,
+            resolver,
+        }
+    }
+
+    /// Parses and evaluates the complete expression.
+    fn parse(mut self) -> Result<f64, ParameterError> {
+        let result = self.expr()?;
+        if !self.rest.trim().is_empty() {
+            return Err(self.error(format!("unexpected \"{}\"", self.rest.trim())));
+        }
+        Ok(result)
+    }
+
+    fn error(&self, reason: String) -> ParameterError {
+        ParameterError::InvalidExpression(self.expr.into(), reason)
+    }
+
+    /// Consumes `token` if the remaining expression starts with it.
+    fn eat(&mut self, token: &str) -> bool {
+        self.rest = self.rest.trim_start();
+        if let Some(rest) = self.rest.strip_prefix(token) {
+            self.rest = rest;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Result<f64, ParameterError> {
+        let mut result = self.term()?;
+        loop {
+            if self.eat("+") {
+                result += self.term()?;
+            } else if self.eat("-") {
+                result -= self.term()?;
+            } else {
+                return Ok(result);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<f64, ParameterError> {
+        let mut result = self.power()?;
+        loop {
+            if self.eat("*") {
+                result *= self.power()?;
+            } else if self.eat("/") {
+                result /= self.power()?;
+            } else {
+                return Ok(result);
+            }
+        }
+    }
+
+    fn power(&mut self) -> Result<f64, ParameterError> {
+        let base = self.unary()?;
+        if self.eat("^") {
+            Ok(base.powf(self.power()?))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64, ParameterError> {
+        if self.eat("-") {
+            Ok(-self.unary()?)
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<f64, ParameterError> {
+        if self.eat("(") {
+            let result = self.expr()?;
+            if !self.eat(")") {
+                return Err(self.error("missing \")\"".into()));
+            }
+            return Ok(result);
+        }
+
+        if self.eat("${") {
+            let end = self
+                .rest
+                .find('}')
+                .ok_or_else(|| self.error("missing \"}\"".into()))?;
+            let name = &self.rest[..end];
+            if !is_valid_name(name) {
+                return Err(self.error(format!("invalid parameter name \"{name}\"")));
+            }
+            self.rest = &self.rest[end + 1..];
+            return self.resolver.get_number(name);
+        }
+
+        // Numbers, including the exponent notation
+        let len = self
+            .rest
+            .char_indices()
+            .find(|&(i, c)| {
+                !(c.is_ascii_digit()
+                    || c == '.'
+                    || c == 'e'
+                    || c == 'E'
+                    || ((c == '+' || c == '-') && i > 0 && self.rest[..i].ends_with(['e', 'E'])))
+            })
+            .map_or(self.rest.len(), |(i, _)| i);
+        if len == 0 {
+            return Err(self.error("expected a number, reference or \"(\"".into()));
+        }
+
+        let number = self.rest[..len]
+            .parse()
+            .map_err(|_| self.error(format!("invalid number \"{}\"", &self.rest[..len])))?;
+        self.rest = &self.rest[len..];
+        Ok(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn resolve_json(parameters: Value, mut value: Value) -> Result<Value, ParameterError> {
+        resolve(parameters.as_object().unwrap(), &mut value)?;
+        Ok(value)
+    }
+
+    #[test]
+    fn reference() {
+        let result = resolve_json(
+            json!({"thrust": 2.4e7, "engine": {"isp_vac": 439.0}}),
+            json!({"thrust_vac": "${thrust}", "engines": ["${engine}"], "x": ["time", []]}),
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            json!({"thrust_vac": 2.4e7, "engines": [{"isp_vac": 439.0}], "x": ["time", []]})
+        );
+    }
+
+    #[test]
+    fn expression() {
+        let result = resolve_json(
+            json!({"target_alt": 9.0e4, "offset": "${target_alt} / 2 - 5e3"}),
+            json!([
+                "${target_alt} + 1000",
+                "-(${offset} + 2) * 2^2",
+                "${offset}"
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(result, json!([91000.0, -160008.0, 40000.0]));
+    }
+
+    #[test]
+    fn unknown_parameter() {
+        let result = resolve_json(json!({}), json!("${missing} + 1")).unwrap_err();
+
+        assert_eq!(result, ParameterError::UnknownParameter("missing".into()));
+    }
+
+    #[test]
+    fn circular_reference() {
+        let result =
+            resolve_json(json!({"a": "${b} + 1", "b": "${a}"}), json!("${a}")).unwrap_err();
+
+        assert_eq!(result, ParameterError::CircularReference("a".into()));
+    }
+
+    #[test]
+    fn invalid_expression() {
+        resolve_json(json!({"a": 1}), json!("${a} +")).unwrap_err();
+        resolve_json(json!({"a": 1}), json!("(${a} + 1")).unwrap_err();
+        resolve_json(json!({"a": "time"}), json!("${a} * 2")).unwrap_err();
+    }
+}
@@ -0,0 +1,89 @@
+//! Generates the JSON Schema of the configuration file.
+//!
+//! The schema is derived from the configuration structs with the external
+//! `schemars` crate, so it always follows the serde attributes. The doc
+//! comments are used as descriptions.
+
+use super::ConfigFile;
+use schemars::schema_for;
+use serde_json::{json, Value};
+
+/// Returns the JSON Schema of the configuration file.
+///
+/// As numbers can also be given as expressions referencing the parameters
+/// (see [`super::parameters`]), each number in the schema also accepts a
+/// string containing a reference.
+pub fn schema() -> Value {
+    let mut schema = serde_json::to_value(schema_for!(ConfigFile))
+        .expect("JSON Schema should always be serializable");
+
+    allow_expressions(&mut schema);
+
+    schema
+}
+
+/// Recursively replaces each number schema with a schema accepting either the
+/// number or an expression string.
+fn allow_expressions(schema: &mut Value) {
+    match schema {
+        Value::Object(obj) => {
+            let is_number = match obj.get("type") {
+                Some(Value::String(t)) => t == "number" || t == "integer",
+                Some(Value::Array(t)) => t.iter().any(|t| t == "number" || t == "integer"),
+                _ => false,
+            };
+
+            if is_number {
+                let number = std::mem::take(obj);
+                obj.insert(
+                    "anyOf".into(),
+                    json!([number, { "type": "string", "pattern": "\\$\\{" }]),
+                );
+            } else {
+                obj.values_mut().for_each(allow_expressions);
+            }
+        }
+        Value::Array(arr) => arr.iter_mut().for_each(allow_expressions),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_definitions() {
+        let schema = schema();
+        let definitions = schema["definitions"].as_object().unwrap();
+
+        for name in [
+            "PhaseConfig",
+            "VehicleConfig",
+            "Engine",
+            "Table",
+            "SteeringConfig",
+            "PlanetConfig",
+            "StateVariable",
+        ] {
+            assert!(definitions.contains_key(name), "{name} is missing");
+        }
+
+        let state_variables = definitions["StateVariable"]["oneOf"].as_array().unwrap();
+        assert!(state_variables
+            .iter()
+            .any(|variant| variant["enum"][0] == "time_since_event"));
+    }
+
+    #[test]
+    fn allows_expressions() {
+        let mut schema = json!({ "properties": { "a": { "type": ["number", "null"] } } });
+
+        allow_expressions(&mut schema);
+
+        assert_eq!(
+            schema["properties"]["a"]["anyOf"][1],
+            json!({ "type": "string", "pattern": "\\$\\{" })
+        );
+    }
+}
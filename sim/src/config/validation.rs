@@ -0,0 +1,323 @@
+//! Validates the phase configurations, which can only be checked together
+//! with the configuration inherited from the previous phases.
+
+use super::{AtmosphereModelConfig, PhaseConfig, PlanetConfig};
+use crate::orientation::PlanetOrientation;
+use crate::vehicle::Engine;
+use std::error::Error;
+use std::fmt::Display;
+
+/// Represents an invalid configuration of a phase. The first field is the
+/// name of the phase.
+#[derive(Debug, PartialEq)]
+pub enum ValidationError {
+    /// Third bodies are used with a planet, which is not an earth model.
+    ThirdBodiesWithoutEarth(String),
+    /// Third bodies are used with the `aligned` orientation, whose inertial
+    /// frame is not the J2000 frame of their positions.
+    ThirdBodiesWithoutJ2000(String),
+    /// The state of an engine is changed, but there is no engine with its
+    /// name. The second field is the name of the engine.
+    UnknownEngine(String, String),
+    /// The orientation of the planet frame is only valid for the Earth, but
+    /// the planet is not an earth model.
+    OrientationWithoutEarth(String),
+    /// The Jacchia 1971 atmosphere is used with a planet, which is not an
+    /// earth model.
+    JacchiaWithoutEarth(String),
+    /// A stage is separated, but there is no next stage.
+    NoStageToSeparate(String),
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::ThirdBodiesWithoutEarth(phase) => write!(
+                f,
+                "Phase {phase}: Third bodies are only supported for the earth models"
+            ),
+            ValidationError::ThirdBodiesWithoutJ2000(phase) => write!(
+                f,
+                "Phase {phase}: Third bodies require an epoch or an orientation other than \"aligned\""
+            ),
+            ValidationError::UnknownEngine(phase, name) => {
+                write!(f, "Phase {phase}: There is no engine named \"{name}\"")
+            }
+            ValidationError::OrientationWithoutEarth(phase) => write!(
+                f,
+                "Phase {phase}: The orientation is only valid for the earth models, use \"aligned\""
+            ),
+            ValidationError::JacchiaWithoutEarth(phase) => write!(
+                f,
+                "Phase {phase}: The Jacchia 1971 atmosphere is only valid for the earth models"
+            ),
+            ValidationError::NoStageToSeparate(phase) => {
+                write!(f, "Phase {phase}: There is no stage to separate")
+            }
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Checks the phase configurations in order, while keeping track of the
+/// inherited values.
+pub fn validate(phases: &[PhaseConfig]) -> Result<(), ValidationError> {
+    let mut earth = true;
+    let mut third_bodies = false;
+    let mut jacchia = false;
+    // The orientation is only initialized in the first phase
+    let orientation = match phases.first().and_then(|config| config.init.as_ref()) {
+        Some(init) => match (init.orientation, &init.epoch) {
+            (Some(orientation), _) => orientation,
+            (None, Some(_)) => PlanetOrientation::SiderealTime,
+            (None, None) => PlanetOrientation::Aligned,
+        },
+        None => PlanetOrientation::Aligned,
+    };
+    // The engine names of the vehicle and of each remaining stage
+    let mut engines: Vec<String> = Vec::new();
+    let mut stages: Vec<Vec<String>> = Vec::new();
+
+    for (i, config) in phases.iter().enumerate() {
+        let name = config.name.clone().unwrap_or_else(|| (i + 1).to_string());
+
+        if let Some(planet) = &config.planet_model {
+            earth = planet.is_earth();
+        }
+        if let Some(config) = &config.third_bodies {
+            third_bodies = !config.is_empty();
+        }
+        if let Some(model) = config.atmosphere.as_ref().and_then(|c| c.model.as_ref()) {
+            jacchia = matches!(model, AtmosphereModelConfig::Jacchia1971(_));
+        }
+        if third_bodies && !earth {
+            return Err(ValidationError::ThirdBodiesWithoutEarth(name));
+        }
+        if third_bodies && orientation == PlanetOrientation::Aligned {
+            return Err(ValidationError::ThirdBodiesWithoutJ2000(name));
+        }
+        if orientation != PlanetOrientation::Aligned && !earth {
+            return Err(ValidationError::OrientationWithoutEarth(name));
+        }
+        if jacchia && !earth {
+            return Err(ValidationError::JacchiaWithoutEarth(name));
+        }
+
+        if let Some(vehicle) = &config.vehicle {
+            if let Some(config) = &vehicle.engines {
+                engines = engine_names(config);
+            }
+            if let Some(config) = &vehicle.stages {
+                stages = config
+                    .iter()
+                    .map(|stage| engine_names(stage.engines()))
+                    .collect();
+                if let Some(stage) = stages.first() {
+                    engines.clone_from(stage);
+                }
+            }
+            // Stages can also be separated on the depletion of the propellant,
+            // so the engines of all remaining stages are accepted
+            if let Some(config) = &vehicle.engine_states {
+                let unknown = config.keys().find(|key| {
+                    !engines
+                        .iter()
+                        .chain(stages.iter().flatten())
+                        .any(|e| e == *key)
+                });
+                if let Some(engine) = unknown {
+                    return Err(ValidationError::UnknownEngine(name, engine.clone()));
+                }
+            }
+            if vehicle.separate_stage == Some(true) {
+                if stages.len() < 2 {
+                    return Err(ValidationError::NoStageToSeparate(name));
+                }
+                stages.remove(0);
+                engines.clone_from(&stages[0]);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the names of the named engines.
+fn engine_names(engines: &[Engine]) -> Vec<String> {
+    engines
+        .iter()
+        .filter_map(|engine| engine.name().map(String::from))
+        .collect()
+}
+
+impl PlanetConfig {
+    /// Returns whether the planet is an earth model. Spherical harmonics are
+    /// assumed to be an earth gravity field, as their defaults are the ones of
+    /// the earth.
+    fn is_earth(&self) -> bool {
+        match self {
+            PlanetConfig::Spherical
+            | PlanetConfig::Fisher1960
+            | PlanetConfig::Smithsonian
+            | PlanetConfig::SphericalHarmonics { .. } => true,
+            PlanetConfig::Moon
+            | PlanetConfig::Mars
+            | PlanetConfig::Venus
+            | PlanetConfig::Custom { .. } => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn validate_json(value: serde_json::Value) -> Result<(), ValidationError> {
+        validate(&serde_json::from_value::<Vec<PhaseConfig>>(value).unwrap())
+    }
+
+    #[test]
+    fn third_bodies() {
+        let init = json!({
+            "latitude": 0, "longitude": 0, "azimuth": 90, "altitude": 0,
+            "epoch": "2025-01-01T00:00:00Z", "orientation": "precession_nutation"
+        });
+        assert_eq!(
+            validate_json(json!([
+                { "init": init, "planet_model": "smithsonian", "third_bodies": ["sun", "moon"] },
+                { "third_bodies": [] },
+                { "planet_model": "mars" }
+            ])),
+            Err(ValidationError::OrientationWithoutEarth("3".into()))
+        );
+        assert_eq!(
+            validate_json(json!([
+                { "init": init, "third_bodies": ["sun"] },
+                { "name": "Landing", "planet_model": "moon" }
+            ])),
+            Err(ValidationError::ThirdBodiesWithoutEarth("Landing".into()))
+        );
+
+        // The positions are given in J2000
+        assert_eq!(
+            validate_json(json!([
+                { "planet_model": "smithsonian", "third_bodies": ["sun", "moon"] },
+                { "third_bodies": [] }
+            ])),
+            Err(ValidationError::ThirdBodiesWithoutJ2000("1".into()))
+        );
+        assert_eq!(
+            validate_json(json!([
+                { "init": init, "planet_model": "smithsonian", "third_bodies": ["sun", "moon"] },
+                { "third_bodies": [] }
+            ])),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn orientation() {
+        let init = |orientation: serde_json::Value| {
+            json!({
+                "latitude": 0, "longitude": 0, "azimuth": 90, "altitude": 0,
+                "epoch": "2025-01-01T00:00:00Z", "orientation": orientation
+            })
+        };
+        assert_eq!(
+            validate_json(json!([
+                { "planet_model": "mars", "init": init(json!("aligned")) }
+            ])),
+            Ok(())
+        );
+        // Defaults to the sidereal time with an epoch
+        assert_eq!(
+            validate_json(json!([
+                { "planet_model": "smithsonian", "init": init(json!(null)) },
+                { "name": "Mars", "planet_model": "mars" }
+            ])),
+            Err(ValidationError::OrientationWithoutEarth("Mars".into()))
+        );
+    }
+
+    #[test]
+    fn jacchia() {
+        let atmosphere = json!({ "model": { "jacchia_1971": {
+            "solar_flux": 150, "solar_flux_average": 150, "geomagnetic_index": 2
+        }}});
+        assert_eq!(
+            validate_json(json!([
+                { "planet_model": "smithsonian", "atmosphere": atmosphere },
+                { "atmosphere": { "model": "planet" } },
+                { "planet_model": "mars" }
+            ])),
+            Ok(())
+        );
+        assert_eq!(
+            validate_json(json!([
+                { "planet_model": "smithsonian", "atmosphere": atmosphere },
+                { "name": "Mars", "planet_model": "mars" }
+            ])),
+            Err(ValidationError::JacchiaWithoutEarth("Mars".into()))
+        );
+    }
+
+    #[test]
+    fn engine_states() {
+        let engine = |name: &str| {
+            json!({
+                "name": name, "incidence": [0, 0], "thrust_vac": 1e5, "isp_vac": 300, "exit_area": 0
+            })
+        };
+        assert_eq!(
+            validate_json(json!([
+                { "vehicle": { "engines": [engine("center"), engine("outer")] } },
+                { "vehicle": { "engine_states": { "center": { "ignited": false } } } },
+                { "vehicle": { "stages": [
+                    { "structure_mass": 1000, "propellant_mass": 2000, "engines": [engine("booster")] },
+                    { "structure_mass": 500, "propellant_mass": 1000, "engines": [engine("upper")] }
+                ]}},
+                { "vehicle": { "engine_states": { "upper": { "throttle": 0.8 } } } }
+            ])),
+            Ok(())
+        );
+        assert_eq!(
+            validate_json(json!([
+                { "vehicle": { "engines": [engine("center")] } },
+                { "name": "Failure", "vehicle": { "engine_states": { "outer": { "ignited": false } } } }
+            ])),
+            Err(ValidationError::UnknownEngine(
+                "Failure".into(),
+                "outer".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn separate_stage() {
+        let stage = json!({ "structure_mass": 1000, "propellant_mass": 2000, "engines": [] });
+        assert_eq!(
+            validate_json(json!([
+                { "vehicle": { "stages": [stage, stage] } },
+                { "vehicle": { "separate_stage": true } },
+                { "vehicle": { "separate_stage": false } }
+            ])),
+            Ok(())
+        );
+        assert_eq!(
+            validate_json(json!([
+                { "vehicle": { "stages": [stage, stage] } },
+                { "vehicle": { "separate_stage": true } },
+                { "name": "Separation", "vehicle": { "separate_stage": true } }
+            ])),
+            Err(ValidationError::NoStageToSeparate("Separation".into()))
+        );
+        assert_eq!(
+            validate_json(json!([
+                { "vehicle": { "separate_stage": true } }
+            ])),
+            Err(ValidationError::NoStageToSeparate("1".into()))
+        );
+    }
+}
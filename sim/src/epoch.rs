@@ -0,0 +1,250 @@
+//! Defines the [`Epoch`], the date and time at the start of the simulation.
+//!
+//! The epoch is either given as Julian date (TT), or as calendar date in the
+//! form `YYYY-MM-DDThh:mm:ss[.sss][Z| UTC| TT]`. Dates without a time scale
+//! are UTC. UTC is converted to TT with the table of leap seconds, which is
+//! valid from 1972 on.
+//!
+//! The difference between UT1 and UTC (below 0.9 s) is neglected.
+
+use crate::utils::constants::{JULIAN_DATE_J2000, SECONDS_PER_DAY};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::error::Error;
+use std::fmt::Display;
+
+/// The difference between TT and TAI in sec.
+const TT_MINUS_TAI: f64 = 32.184;
+
+/// The leap seconds as the Julian date (UTC) of their introduction and the
+/// difference between TAI and UTC in sec afterwards.
+const LEAP_SECONDS: [(f64, f64); 28] = [
+    (2441317.5, 10.), // 1972-01-01
+    (2441499.5, 11.), // 1972-07-01
+    (2441683.5, 12.), // 1973-01-01
+    (2442048.5, 13.), // 1974-01-01
+    (2442413.5, 14.), // 1975-01-01
+    (2442778.5, 15.), // 1976-01-01
+    (2443144.5, 16.), // 1977-01-01
+    (2443509.5, 17.), // 1978-01-01
+    (2443874.5, 18.), // 1979-01-01
+    (2444239.5, 19.), // 1980-01-01
+    (2444786.5, 20.), // 1981-07-01
+    (2445151.5, 21.), // 1982-07-01
+    (2445516.5, 22.), // 1983-07-01
+    (2446247.5, 23.), // 1985-07-01
+    (2447161.5, 24.), // 1988-01-01
+    (2447892.5, 25.), // 1990-01-01
+    (2448257.5, 26.), // 1991-01-01
+    (2448804.5, 27.), // 1992-07-01
+    (2449169.5, 28.), // 1993-07-01
+    (2449534.5, 29.), // 1994-07-01
+    (2450083.5, 30.), // 1996-01-01
+    (2450630.5, 31.), // 1997-07-01
+    (2451179.5, 32.), // 1999-01-01
+    (2453736.5, 33.), // 2006-01-01
+    (2454832.5, 34.), // 2009-01-01
+    (2456109.5, 35.), // 2012-07-01
+    (2457204.5, 36.), // 2015-07-01
+    (2457754.5, 37.), // 2017-01-01
+];
+
+/// Represents an error while parsing the epoch.
+#[derive(Debug, PartialEq)]
+pub struct EpochError(String);
+
+impl Display for EpochError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Invalid epoch \"{}\", expected \"YYYY-MM-DDThh:mm:ss[.sss][Z| UTC| TT]\"",
+            self.0
+        )
+    }
+}
+
+impl Error for EpochError {}
+
+/// Represents the epoch as Julian date (TT).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "EpochUnchecked")]
+pub struct Epoch(pub f64);
+
+impl Default for Epoch {
+    fn default() -> Self {
+        Self(JULIAN_DATE_J2000)
+    }
+}
+
+/// The configuration of the [`Epoch`].
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum EpochUnchecked {
+    /// Julian date (TT).
+    JulianDate(f64),
+    /// Calendar date in the form `YYYY-MM-DDThh:mm:ss[.sss][Z| UTC| TT]`,
+    /// e.g. `"2024-05-01T12:30:00Z"`. Defaults to UTC.
+    Date(String),
+}
+
+impl TryFrom<EpochUnchecked> for Epoch {
+    type Error = EpochError;
+
+    fn try_from(value: EpochUnchecked) -> Result<Self, Self::Error> {
+        match value {
+            EpochUnchecked::JulianDate(julian_date) => Ok(Self(julian_date)),
+            EpochUnchecked::Date(date) => Self::parse(&date).ok_or(EpochError(date)),
+        }
+    }
+}
+
+impl JsonSchema for Epoch {
+    fn schema_name() -> String {
+        "Epoch".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        EpochUnchecked::json_schema(gen)
+    }
+}
+
+impl Epoch {
+    /// Parses a calendar date in the form
+    /// `YYYY-MM-DDThh:mm:ss[.sss][Z| UTC| TT]`.
+    fn parse(date: &str) -> Option<Self> {
+        let (date, is_tt) = if let Some(date) = date.strip_suffix(" TT") {
+            (date, true)
+        } else if let Some(date) = date.strip_suffix(" UTC") {
+            (date, false)
+        } else {
+            (date.strip_suffix('Z').unwrap_or(date), false)
+        };
+
+        let (date, time) = date.split_once('T')?;
+        let mut date = date.splitn(3, '-').map(str::parse::<i32>);
+        let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+        let mut time = time.splitn(3, ':').map(str::parse::<f64>);
+        let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+
+        if !(1..=12).contains(&month)
+            || !(1..=31).contains(&day)
+            || !(0. ..24.).contains(&hour)
+            || !(0. ..60.).contains(&minute)
+            // Allow leap seconds
+            || !(0. ..61.).contains(&second)
+        {
+            return None;
+        }
+
+        let julian_date = julian_date(year, month, day)
+            + (hour * 3600. + minute * 60. + second) / SECONDS_PER_DAY;
+
+        if is_tt {
+            Some(Self(julian_date))
+        } else {
+            Some(Self(utc_to_tt(julian_date)))
+        }
+    }
+}
+
+/// Calculates the Julian date at 0:00 of the date in the Gregorian calendar,
+/// see Meeus: "Astronomical Algorithms", p. 61.
+fn julian_date(year: i32, month: i32, day: i32) -> f64 {
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 12)
+    } else {
+        (year, month)
+    };
+    let a = year.div_euclid(100);
+    let b = 2 - a + a.div_euclid(4);
+
+    (365.25 * (year + 4716) as f64).floor()
+        + (30.6001 * (month + 1) as f64).floor()
+        + (day + b) as f64
+        - 1524.5
+}
+
+/// Returns the difference between TAI and UTC in sec at the Julian date (UTC).
+/// Before 1972, the first value is used.
+fn tai_minus_utc(julian_date_utc: f64) -> f64 {
+    LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|(introduction, _)| julian_date_utc >= *introduction)
+        .unwrap_or(&LEAP_SECONDS[0])
+        .1
+}
+
+/// Converts a Julian date from UTC to TT.
+fn utc_to_tt(julian_date_utc: f64) -> f64 {
+    julian_date_utc + (tai_minus_utc(julian_date_utc) + TT_MINUS_TAI) / SECONDS_PER_DAY
+}
+
+/// Converts a Julian date from TT to UTC. This is also used as UT1.
+pub fn tt_to_utc(julian_date_tt: f64) -> f64 {
+    let julian_date_tai = julian_date_tt - TT_MINUS_TAI / SECONDS_PER_DAY;
+
+    // The introduction of the leap seconds in TAI
+    let tai_minus_utc = LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|(introduction, tai_minus_utc)| {
+            julian_date_tai >= introduction + tai_minus_utc / SECONDS_PER_DAY
+        })
+        .unwrap_or(&LEAP_SECONDS[0])
+        .1;
+
+    julian_date_tai - tai_minus_utc / SECONDS_PER_DAY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_almost_eq_rel;
+
+    #[test]
+    fn calendar_date() {
+        // See Meeus: "Astronomical Algorithms", ex. 7.a
+        assert_eq!(julian_date(1957, 10, 4), 2436115.5);
+        assert_eq!(julian_date(2000, 1, 1), 2451544.5);
+        assert_eq!(julian_date(1600, 12, 31), 2305812.5);
+
+        assert_eq!(
+            Epoch::parse("2000-01-01T12:00:00 TT"),
+            Some(Epoch(JULIAN_DATE_J2000))
+        );
+        // 32 leap seconds and 32.184 sec difference of TT to TAI
+        let epoch = Epoch::parse("2000-01-01T11:58:55.816Z").unwrap();
+        assert_almost_eq_rel!(epoch.0, JULIAN_DATE_J2000, 1e-14);
+        let epoch = Epoch::parse("2000-01-01T11:58:55.816 UTC").unwrap();
+        assert_almost_eq_rel!(epoch.0, JULIAN_DATE_J2000, 1e-14);
+        assert_almost_eq_rel!(tt_to_utc(epoch.0), 2451544.99925713_f64, 1e-14);
+
+        assert_eq!(Epoch::parse("2000-01-01"), None);
+        assert_eq!(Epoch::parse("2000-13-01T00:00:00"), None);
+    }
+
+    #[test]
+    fn leap_seconds() {
+        let before = Epoch::parse("2016-12-31T23:59:59Z").unwrap().0;
+        let after = Epoch::parse("2017-01-01T00:00:00Z").unwrap().0;
+
+        // A leap second was inserted before 2017
+        assert_almost_eq_rel!((after - before) * SECONDS_PER_DAY, 2_f64, 1e-4);
+        assert_almost_eq_rel!(tt_to_utc(before), 2457754.5 - 1. / SECONDS_PER_DAY, 1e-14);
+        assert_almost_eq_rel!(tt_to_utc(after), 2457754.5_f64, 1e-14);
+    }
+
+    #[test]
+    fn deserialize() {
+        let epoch: Epoch = serde_json::from_str("2451545.5").unwrap();
+        assert_eq!(epoch, Epoch(2451545.5));
+
+        let epoch: Epoch = serde_json::from_str("\"2000-01-01T12:00:00 TT\"").unwrap();
+        assert_eq!(epoch, Epoch(JULIAN_DATE_J2000));
+
+        serde_json::from_str::<Epoch>("\"yesterday\"").unwrap_err();
+    }
+}
@@ -20,7 +20,7 @@ mod steering {
 
 mod data {
     use lazy_static::lazy_static;
-    use nalgebra::Vector3;
+    use nalgebra::{Rotation3, Vector3};
 
     use crate::planet::EARTH_SPHERICAL;
     use crate::utils::constants::*;
@@ -65,7 +65,7 @@ mod data {
         }
 
         pub fn velocity_planet(&self) -> Vector3<f64> {
-            EARTH_SPHERICAL.velocity_planet(self.position, self.velocity)
+            EARTH_SPHERICAL.velocity_planet(self.position, self.velocity, &Rotation3::identity())
         }
     }
 
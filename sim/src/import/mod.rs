@@ -0,0 +1,660 @@
+//! Converts legacy POST namelist input decks into the configuration format.
+//!
+//! The deck is parsed with [`namelist::parse`]. Each `$GENDAT` group starts
+//! a new phase, and `$TAB` groups add aerodynamic tables to the phase of the
+//! preceding `$GENDAT` group. All values are converted from English units to
+//! SI units. Inputs which cannot be converted are collected in
+//! [`ImportedDeck::unsupported`], so they can be reported to the user.
+//!
+//! The supported `$GENDAT` inputs are:
+//! - Events: `event`, `critr` and `value` (see [`variable`] for the supported
+//!   criteria), as well as `dt`. The control flags `fesn`, `endphs`, `endprb`
+//!   and `endjob` are ignored.
+//! - Initialization: `gdlat`, `long` and `azl` in °, `alt` in ft.
+//! - Vehicle: `wstpd(1)` and `wprp(1)` in lb, `sref` in ft^2, `asmax` in
+//!   ft/s^2.
+//! - Engines: `tvc1(i)` in lbf, `ispv(i)` in sec, `ae(i)` in ft^2,
+//!   `epitch(i)` and `eyaw(i)` in °, and `iengmf(i)` to switch an engine off
+//!   (`0`) or on (`1`). Switched off engines are kept with `"ignited": false`,
+//!   so the engines keep their order. Engine inputs are kept for the
+//!   following events, as in POST.
+//! - Polynomial steering: the coefficients `pitpc(1..4)`, `yawpc(1..4)` and
+//!   `rolpc(1..4)` in °/X^i, and the independent variables `pitvr`, `yawvr`
+//!   and `rolvr` (defaults to `'tdurp'`). The constant term is inherited from
+//!   the previous phase, so `pitpc(1)` etc. must be zero.
+//! - Flags: `npc(5)` for the atmosphere (`0`: none, `2`: 1962 standard
+//!   atmosphere) and `npc(16)` for the planet (`0`: spherical, `1`: Fisher
+//!   1960, `2`: Smithsonian). A custom planet is defined with `re` and `rp`
+//!   in ft, `mu` in ft^3/s^2, `j2` to `j4` and `omega` in rad/s.
+//!
+//! Tables are defined in `$TAB` groups with
+//! `table = 'cdt', ndim, 'x', ['y', ['z',]] nx, [ny, [nz,]] ...`, followed by
+//! optional interpolation flags, the bases of each variable and the data. The
+//! data is listed with the last variable changing fastest. Supported tables
+//! are `cdt`, `clt` and `cyt`. Interpolation flags other than `1` (linear)
+//! are reported as unsupported.
+
+mod namelist;
+
+use crate::utils::constants::*;
+use namelist::{Assignment, Group, Value as NamelistValue};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt::Display;
+
+/// Represents an error while importing a deck.
+#[derive(Debug, PartialEq)]
+pub enum ImportError {
+    /// The namelist syntax is invalid in the given line.
+    Syntax(usize, String),
+    /// A supported input has an invalid value.
+    InvalidInput(String),
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Syntax(line, reason) => write!(f, "Syntax error in line {line}: {reason}"),
+            ImportError::InvalidInput(reason) => write!(f, "Invalid input: {reason}"),
+        }
+    }
+}
+
+impl Error for ImportError {}
+
+/// Represents the result of an import.
+#[derive(Debug)]
+pub struct ImportedDeck {
+    /// The configuration file content, with the current version.
+    pub config: Value,
+    /// A description of each input which could not be converted.
+    pub unsupported: Vec<String>,
+}
+
+/// Converts a POST namelist input deck into a configuration file.
+pub fn import_deck(input: &str) -> Result<ImportedDeck, ImportError> {
+    let mut importer = Importer::default();
+
+    for group in namelist::parse(input)? {
+        match group.name.as_str() {
+            "gendat" => importer.gendat(&group)?,
+            "tab" => importer.tab(&group)?,
+            "search" => importer.unsupported.push(format!(
+                "$SEARCH (line {}): optimization is not supported, {} input(s) ignored",
+                group.line,
+                group.assignments.len()
+            )),
+            name => importer.unsupported.push(format!(
+                "${} (line {}): group is not supported",
+                name.to_uppercase(),
+                group.line
+            )),
+        }
+    }
+
+    let phases: Vec<Value> = importer.phases.into_iter().map(Value::Object).collect();
+    let config = json!({ "version": crate::config::VERSION, "phases": phases });
+
+    // Make sure the result is a valid configuration
+    crate::config::from_value(config.clone())
+        .map_err(|err| ImportError::InvalidInput(err.to_string()))?;
+
+    Ok(ImportedDeck {
+        config,
+        unsupported: importer.unsupported,
+    })
+}
+
+/// Returns the state variable of a POST variable name, together with the
+/// factor to convert its value to SI units.
+fn variable(name: &str) -> Option<(&'static str, f64)> {
+    Some(match name {
+        "time" => ("time", 1.),
+        "tdurp" => ("time_since_event", 1.),
+        "altito" => ("altitude", METER_PER_FOOT),
+        "gdalt" => ("altitude_geodetic", METER_PER_FOOT),
+        "gdlat" => ("latitude_geodetic", PI / 180.),
+        "long" => ("longitude", PI / 180.),
+        "weight" => ("mass", KILOGRAM_PER_POUND),
+        "wprp" | "wprop" => ("propellant_mass", KILOGRAM_PER_POUND),
+        "veli" => ("velocity_norm", METER_PER_FOOT),
+        "velr" => ("velocity_planet_norm", METER_PER_FOOT),
+        "mach" => ("mach_number", 1.),
+        "dynp" => ("dynamic_pressure", PASCAL_PER_PSF),
+        "alpha" => ("alpha", PI / 180.),
+        _ => return None,
+    })
+}
+
+/// Represents the engine inputs, which are kept over all events.
+#[derive(Debug, Clone)]
+struct EngineInput {
+    /// Vacuum thrust in lbf.
+    thrust: f64,
+    /// Vacuum specific impulse in sec.
+    isp: f64,
+    /// Exit area in ft^2.
+    exit_area: f64,
+    /// Pitch and yaw incidence in °.
+    incidence: [f64; 2],
+    /// Whether the engine is switched on.
+    on: bool,
+}
+
+impl Default for EngineInput {
+    fn default() -> Self {
+        Self {
+            thrust: 0.,
+            isp: 0.,
+            exit_area: 0.,
+            incidence: [0., 0.],
+            on: true,
+        }
+    }
+}
+
+/// Represents the polynomial steering inputs of one axis in one event.
+#[derive(Debug)]
+struct SteeringInput {
+    /// The POST name of the independent variable.
+    variable: String,
+    /// The coefficients in ascending order, including the constant term.
+    coeffs: [f64; 4],
+}
+
+impl Default for SteeringInput {
+    fn default() -> Self {
+        Self {
+            variable: "tdurp".into(),
+            coeffs: [0.; 4],
+        }
+    }
+}
+
+/// Helper struct holding the state of the conversion.
+#[derive(Debug, Default)]
+struct Importer {
+    /// The converted phase configurations.
+    phases: Vec<Map<String, Value>>,
+    /// The engine inputs of all events so far.
+    engines: Vec<EngineInput>,
+    /// The inputs which could not be converted.
+    unsupported: Vec<String>,
+}
+
+impl Importer {
+    /// Converts a `$GENDAT` group into a new phase.
+    fn gendat(&mut self, group: &Group) -> Result<(), ImportError> {
+        let mut context = format!("$GENDAT (line {})", group.line);
+        let mut phase = Map::new();
+        let mut vehicle = Map::new();
+        let mut init = Map::new();
+        let mut planet = HashMap::new();
+        let mut steering: [Option<SteeringInput>; 3] = Default::default();
+        let mut criterion = None;
+        let mut criterion_value = None;
+        let mut engines_changed = false;
+
+        for assignment in group.assignments.iter() {
+            let Assignment {
+                name,
+                index,
+                values,
+            } = assignment;
+            let name = name.as_str();
+
+            match name {
+                "event" => {
+                    let event = number(assignment, 0)?;
+                    context = format!("$GENDAT event {event} (line {})", group.line);
+                    phase.insert("name".into(), json!(format!("event {event}")));
+                }
+                "fesn" | "endphs" | "endprb" | "endjob" => (),
+                "critr" => criterion = Some(string(assignment, 0)?),
+                "value" => criterion_value = Some(number(assignment, 0)?),
+                "dt" => {
+                    phase.insert("stepsize".into(), json!(number(assignment, 0)?));
+                }
+                "gdlat" | "long" | "azl" => {
+                    let key = match name {
+                        "gdlat" => "latitude",
+                        "long" => "longitude",
+                        _ => "azimuth",
+                    };
+                    init.insert(key.into(), json!(number(assignment, 0)?));
+                }
+                "alt" => {
+                    init.insert(
+                        "altitude".into(),
+                        json!(number(assignment, 0)? * METER_PER_FOOT),
+                    );
+                }
+                "wstpd" | "wprp" if *index == 1 && values.len() == 1 => {
+                    let key = match name {
+                        "wstpd" => "structure_mass",
+                        _ => "propellant_mass",
+                    };
+                    vehicle.insert(
+                        key.into(),
+                        json!(number(assignment, 0)? * KILOGRAM_PER_POUND),
+                    );
+                }
+                "sref" => {
+                    vehicle.insert(
+                        "reference_area".into(),
+                        json!(number(assignment, 0)? * SQUARE_METER_PER_SQUARE_FOOT),
+                    );
+                }
+                "asmax" => {
+                    phase.insert(
+                        "max_acceleration".into(),
+                        json!(number(assignment, 0)? * METER_PER_FOOT),
+                    );
+                }
+                "tvc1" | "ispv" | "ae" | "epitch" | "eyaw" | "iengmf" => {
+                    engines_changed = true;
+                    for (i, _) in values.iter().enumerate() {
+                        let value = number(assignment, i)?;
+                        let idx = index + i - 1;
+                        if self.engines.len() <= idx {
+                            self.engines.resize(idx + 1, EngineInput::default());
+                        }
+                        let engine = &mut self.engines[idx];
+                        match name {
+                            "tvc1" => engine.thrust = value,
+                            "ispv" => engine.isp = value,
+                            "ae" => engine.exit_area = value,
+                            "epitch" => engine.incidence[0] = value,
+                            "eyaw" => engine.incidence[1] = value,
+                            _ => engine.on = value != 0.,
+                        }
+                    }
+                }
+                "pitpc" | "yawpc" | "rolpc" | "pitvr" | "yawvr" | "rolvr" => {
+                    let axis = match &name[..3] {
+                        "rol" => 0,
+                        "yaw" => 1,
+                        _ => 2,
+                    };
+                    let input = steering[axis].get_or_insert_with(Default::default);
+                    if name.ends_with("vr") {
+                        input.variable = string(assignment, 0)?;
+                    } else if index + values.len() - 1 > 4 {
+                        return Err(invalid(assignment, "only 4 coefficients are supported"));
+                    } else {
+                        for i in 0..values.len() {
+                            input.coeffs[index + i - 1] = number(assignment, i)?;
+                        }
+                    }
+                }
+                "npc" if *index == 5 && values.len() == 1 => {
+                    match number(assignment, 0)? as i64 {
+                        0 => phase.insert("atmosphere".into(), json!({ "model": "none" })),
+                        2 => phase.insert(
+                            "atmosphere".into(),
+                            json!({ "model": "standard_atmosphere_1962" }),
+                        ),
+                        flag => {
+                            self.unsupported.push(format!(
+                                "{context}: atmosphere model npc(5) = {flag} is not supported"
+                            ));
+                            None
+                        }
+                    };
+                }
+                "npc" if *index == 16 && values.len() == 1 => {
+                    match number(assignment, 0)? as i64 {
+                        0 => phase.insert("planet_model".into(), json!("spherical")),
+                        1 => phase.insert("planet_model".into(), json!("fisher1960")),
+                        2 => phase.insert("planet_model".into(), json!("smithsonian")),
+                        flag => {
+                            self.unsupported.push(format!(
+                                "{context}: gravity model npc(16) = {flag} is not supported"
+                            ));
+                            None
+                        }
+                    };
+                }
+                "re" | "rp" | "mu" | "j2" | "j3" | "j4" | "omega" => {
+                    planet.insert(name, number(assignment, 0)?);
+                }
+                _ => {
+                    let name = if *index == 1 && values.len() == 1 {
+                        name.to_string()
+                    } else {
+                        format!("{name}({index})")
+                    };
+                    self.unsupported
+                        .push(format!("{context}: input \"{name}\" is not supported"));
+                }
+            }
+        }
+
+        match (criterion, criterion_value) {
+            (Some(criterion), Some(value)) => {
+                let Some((variable, factor)) = variable(&criterion) else {
+                    return Err(ImportError::InvalidInput(format!(
+                        "{context}: criterion \"{criterion}\" is not supported"
+                    )));
+                };
+                phase.insert("end_criterion".into(), json!([variable, value * factor]));
+            }
+            (None, None) => (),
+            _ => {
+                return Err(ImportError::InvalidInput(format!(
+                    "{context}: \"critr\" and \"value\" must be given together"
+                )))
+            }
+        }
+
+        if !init.is_empty() {
+            for key in ["latitude", "longitude", "azimuth", "altitude"] {
+                init.entry(key).or_insert(json!(0.));
+            }
+            phase.insert("init".into(), Value::Object(init));
+        }
+
+        if !planet.is_empty() {
+            let get = |key: &str| {
+                planet.get(key).copied().ok_or_else(|| {
+                    ImportError::InvalidInput(format!(
+                        "{context}: \"{key}\" is missing for the custom planet"
+                    ))
+                })
+            };
+            phase.insert(
+                "planet_model".into(),
+                json!({ "custom": {
+                    "equatorial_radius": get("re")? * METER_PER_FOOT,
+                    "polar_radius": get("rp")? * METER_PER_FOOT,
+                    "gravitational_parameters": [
+                        get("mu")? * CUBIC_METER_PER_CUBIC_FOOT,
+                        planet.get("j2").unwrap_or(&0.),
+                        planet.get("j3").unwrap_or(&0.),
+                        planet.get("j4").unwrap_or(&0.),
+                    ],
+                    "rotation_rate": get("omega")?,
+                }}),
+            );
+        }
+
+        if steering.iter().any(Option::is_some) {
+            let mut config = Map::new();
+            for (key, input) in ["roll", "yaw", "pitch"].into_iter().zip(steering) {
+                let Some(input) = input else {
+                    continue;
+                };
+                let Some((variable, factor)) = variable(&input.variable) else {
+                    return Err(ImportError::InvalidInput(format!(
+                        "{context}: steering variable \"{}\" is not supported",
+                        input.variable
+                    )));
+                };
+                if input.coeffs[0] != 0. {
+                    self.unsupported.push(format!(
+                        "{context}: constant {key} steering coefficient is ignored, as it is \
+                        inherited from the previous phase"
+                    ));
+                }
+                let coeffs: Vec<f64> = (1..4)
+                    .map(|i| input.coeffs[i] / factor.powi(i as i32))
+                    .collect();
+                config.insert(key.into(), json!([variable, coeffs]));
+            }
+            phase.insert("steering".into(), Value::Object(config));
+        }
+
+        if engines_changed {
+            let engines: Vec<Value> = self
+                .engines
+                .iter()
+                .map(|engine| {
+                    json!({
+                        "incidence": engine.incidence.map(f64::to_radians),
+                        "thrust_vac": engine.thrust * NEWTON_PER_POUND_FORCE,
+                        "isp_vac": engine.isp,
+                        "exit_area": engine.exit_area * SQUARE_METER_PER_SQUARE_FOOT,
+                        "ignited": engine.on,
+                    })
+                })
+                .collect();
+            vehicle.insert("engines".into(), Value::Array(engines));
+        }
+
+        if !vehicle.is_empty() {
+            phase.insert("vehicle".into(), Value::Object(vehicle));
+        }
+
+        self.phases.push(phase);
+        Ok(())
+    }
+
+    /// Converts the tables of a `$TAB` group and adds them to the vehicle of
+    /// the last phase.
+    fn tab(&mut self, group: &Group) -> Result<(), ImportError> {
+        let context = format!("$TAB (line {})", group.line);
+
+        for assignment in group.assignments.iter() {
+            if assignment.name != "table" {
+                self.unsupported.push(format!(
+                    "{context}: input \"{}\" is not supported",
+                    assignment.name
+                ));
+                continue;
+            }
+
+            let name = string(assignment, 0)?;
+            let key = match name.as_str() {
+                "cdt" => "drag_coeff",
+                "clt" => "lift_coeff",
+                "cyt" => "side_force_coeff",
+                _ => {
+                    self.unsupported
+                        .push(format!("{context}: table \"{name}\" is not supported"));
+                    continue;
+                }
+            };
+
+            let Some(phase) = self.phases.last_mut() else {
+                return Err(ImportError::InvalidInput(format!(
+                    "{context}: tables must follow a $GENDAT group"
+                )));
+            };
+
+            let (table, flags) = table(assignment)?;
+            if flags.iter().any(|flag| *flag != 1.) {
+                self.unsupported.push(format!(
+                    "{context}: interpolation flags of table \"{name}\" are not supported, \
+                    linear interpolation is used"
+                ));
+            }
+
+            phase
+                .entry("vehicle")
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()
+                .expect("the vehicle should always be an object")
+                .insert(key.into(), table);
+        }
+
+        Ok(())
+    }
+}
+
+/// Converts a `table = ...` assignment. Returns the table and its
+/// interpolation flags.
+fn table(assignment: &Assignment) -> Result<(Value, Vec<f64>), ImportError> {
+    let ndim = number(assignment, 1)? as usize;
+    if !(1..=3).contains(&ndim) {
+        return Err(invalid(assignment, "tables must have 1 to 3 dimensions"));
+    }
+
+    let mut variables = Vec::new();
+    let mut sizes = Vec::new();
+    for i in 0..ndim {
+        let name = string(assignment, 2 + i)?;
+        let variable = variable(&name)
+            .ok_or_else(|| invalid(assignment, &format!("variable \"{name}\" is not supported")))?;
+        variables.push(variable);
+        sizes.push(number(assignment, 2 + ndim + i)? as usize);
+    }
+
+    let rest = (2 + 2 * ndim..assignment.values.len())
+        .map(|i| number(assignment, i))
+        .collect::<Result<Vec<_>, _>>()?;
+    let expected = sizes.iter().sum::<usize>() + sizes.iter().product::<usize>();
+    if rest.len() < expected || rest.len() - expected > ndim + 1 {
+        return Err(invalid(
+            assignment,
+            &format!("expected {expected} bases and data points"),
+        ));
+    }
+    let (flags, mut rest) = rest.split_at(rest.len() - expected);
+
+    let mut table = Map::new();
+    for (key, ((variable, factor), size)) in ["x", "y", "z"]
+        .into_iter()
+        .zip(variables.iter().zip(&sizes))
+    {
+        let bases: Vec<f64> = rest[..*size].iter().map(|base| base * factor).collect();
+        rest = &rest[*size..];
+        table.insert(key.into(), json!([variable, bases]));
+    }
+
+    // Nest the data, starting with the last (fastest changing) variable
+    let mut data: Vec<Value> = rest.iter().map(|value| json!(value)).collect();
+    for size in sizes[1..].iter().rev() {
+        data = data
+            .chunks(*size)
+            .map(|chunk| Value::Array(chunk.to_vec()))
+            .collect();
+    }
+    table.insert("data".into(), Value::Array(data));
+
+    Ok((Value::Object(table), flags.to_vec()))
+}
+
+/// Returns an error for an invalid assignment.
+fn invalid(assignment: &Assignment, reason: &str) -> ImportError {
+    ImportError::InvalidInput(format!("\"{}\": {reason}", assignment.name))
+}
+
+/// Returns the i-th value of an assignment as number.
+fn number(assignment: &Assignment, i: usize) -> Result<f64, ImportError> {
+    match assignment.values.get(i) {
+        Some(NamelistValue::Number(number)) => Ok(*number),
+        _ => Err(invalid(
+            assignment,
+            &format!("value {} must be a number", i + 1),
+        )),
+    }
+}
+
+/// Returns the i-th value of an assignment as lower case string.
+fn string(assignment: &Assignment, i: usize) -> Result<String, ImportError> {
+    match assignment.values.get(i) {
+        Some(NamelistValue::String(str)) => Ok(str.trim().to_lowercase()),
+        _ => Err(invalid(
+            assignment,
+            &format!("value {} must be a string", i + 1),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_almost_eq_rel;
+
+    const DECK: &str = "
+        Two event ascent
+         $search srchm = 4, ipro = -1 $
+         $gendat
+          event = 1, fesn = 2, critr = 'tdurp', value = 15.,
+          dt = 5., npc(5) = 2, npc(16) = 0,
+          gdlat = 28.5, long = 279.4, azl = 90., alt = 0.,
+          wstpd(1) = 1.0d6, wprp(1) = 2.0d6, sref = 4500.,
+          tvc1(1) = 2*1.0e6, ispv(1) = 2*400., ae(1) = 2*100.,
+          pinc = 10.,
+         $
+         $tab table = 'cdt', 2, 'alpha', 'mach', 2, 3, 1, 1, 1,
+          0., 10.,
+          0., 1., 2.,
+          0.2, 0.4, 0.3,
+          0.3, 0.5, 0.4,
+         $
+         $gendat
+          event = 2, critr = 'altito', value = 1.0e5,
+          pitvr = 'time', pitpc(2) = -0.5, 0., 1.0e-3,
+          iengmf(2) = 0, asmax = 96.5,
+         $
+    ";
+
+    #[test]
+    fn import() {
+        let result = import_deck(DECK).unwrap();
+        let config = &result.config["phases"];
+
+        assert_eq!(config.as_array().unwrap().len(), 2);
+        assert_eq!(config[0]["name"], "event 1");
+        assert_eq!(config[0]["end_criterion"], json!(["time_since_event", 15.]));
+        assert_eq!(config[0]["atmosphere"]["model"], "standard_atmosphere_1962");
+        assert_eq!(config[0]["init"]["latitude"], 28.5);
+        assert_almost_eq_rel!(
+            config[0]["vehicle"]["structure_mass"].as_f64().unwrap(),
+            4.535924e5_f64,
+            1e-9
+        );
+        assert_eq!(config[0]["vehicle"]["engines"].as_array().unwrap().len(), 2);
+        assert_almost_eq_rel!(
+            config[0]["vehicle"]["engines"][1]["thrust_vac"]
+                .as_f64()
+                .unwrap(),
+            4.448222e6_f64,
+            1e-6
+        );
+
+        let drag = &config[0]["vehicle"]["drag_coeff"];
+        assert_eq!(drag["x"][0], "alpha");
+        assert_almost_eq_rel!(
+            drag["x"][1][1].as_f64().unwrap(),
+            10_f64.to_radians(),
+            1e-12
+        );
+        assert_eq!(drag["data"], json!([[0.2, 0.4, 0.3], [0.3, 0.5, 0.4]]));
+
+        assert_eq!(config[1]["end_criterion"][0], "altitude");
+        assert_almost_eq_rel!(
+            config[1]["end_criterion"][1].as_f64().unwrap(),
+            3.048e4_f64,
+            1e-12
+        );
+        assert_eq!(
+            config[1]["steering"]["pitch"],
+            json!(["time", [-0.5, 0., 1e-3]])
+        );
+        assert_eq!(config[1]["vehicle"]["engines"].as_array().unwrap().len(), 2);
+        assert_eq!(config[1]["vehicle"]["engines"][0]["ignited"], true);
+        assert_eq!(config[1]["vehicle"]["engines"][1]["ignited"], false);
+
+        assert_eq!(result.unsupported.len(), 2);
+        assert!(result.unsupported[0].starts_with("$SEARCH"));
+        assert!(result.unsupported[1].contains("\"pinc\""));
+    }
+
+    #[test]
+    fn invalid_table() {
+        let deck = "$gendat event = 1 $ $tab table = 'cdt', 1, 'mach', 3, 0., 1., 0.2 $";
+
+        import_deck(deck).unwrap_err();
+    }
+
+    #[test]
+    fn unknown_criterion() {
+        let deck = "$gendat event = 1, critr = 'gammai', value = 1. $";
+
+        import_deck(deck).unwrap_err();
+    }
+}
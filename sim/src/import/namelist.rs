@@ -0,0 +1,365 @@
+//! Parses Fortran namelist input as used by the original POST input decks.
+//!
+//! A deck consists of groups like `$GENDAT ... $`, which contain assignments
+//! of the form `name = value, value, ...` or `name(index) = value, ...`.
+//! Groups can also be written as `&GENDAT ... /`, and be terminated by `$END`
+//! or `&END`. Anything outside of the groups (e.g. a title line) is ignored,
+//! as are comments starting with `!`.
+//!
+//! Values are numbers (also with Fortran `D` exponents), quoted strings and
+//! logicals (`.TRUE.`, `T`, ...). Repeated values can be written as `3*0.0`.
+//!
+//! All names are converted to lower case.
+
+use super::ImportError;
+
+/// Represents one namelist group.
+#[derive(Debug, PartialEq)]
+pub struct Group {
+    /// The name of the group, e.g. `gendat`.
+    pub name: String,
+    /// The line in which the group starts.
+    pub line: usize,
+    /// The assignments in the order of the input.
+    pub assignments: Vec<Assignment>,
+}
+
+/// Represents one assignment in a namelist group.
+#[derive(Debug, PartialEq)]
+pub struct Assignment {
+    /// The name of the variable.
+    pub name: String,
+    /// The 1-based index of the first value. It is 1 if no index was given.
+    pub index: usize,
+    /// The assigned values. Multiple values are assigned to consecutive
+    /// indexes.
+    pub values: Vec<Value>,
+}
+
+/// Represents a value of a namelist assignment.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A integer or floating point number.
+    Number(f64),
+    /// A quoted string.
+    String(String),
+    /// A logical value.
+    Logical(bool),
+}
+
+/// Represents a token of the namelist input.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Start of a group, e.g. `$GENDAT`.
+    GroupStart(String),
+    /// End of a group, e.g. `$` or `$END`.
+    GroupEnd,
+    /// A variable name or unquoted logical.
+    Name(String),
+    /// A quoted string.
+    String(String),
+    /// A number, possibly preceded by a repeat count (e.g. `3*0.0`).
+    Number(usize, f64),
+    OpenParen,
+    CloseParen,
+    Equals,
+    Comma,
+}
+
+/// Parses the namelist groups of a deck.
+pub fn parse(input: &str) -> Result<Vec<Group>, ImportError> {
+    let tokens = tokenize(input)?;
+    let mut tokens = tokens.into_iter().peekable();
+
+    let mut groups = Vec::new();
+    while let Some((line, token)) = tokens.next() {
+        let Token::GroupStart(name) = token else {
+            // Ignore everything outside of groups
+            continue;
+        };
+
+        let mut group = Group {
+            name,
+            line,
+            assignments: Vec::new(),
+        };
+
+        loop {
+            let Some((line, token)) = tokens.next() else {
+                return Err(ImportError::Syntax(
+                    line,
+                    format!("group ${} is not closed", group.name),
+                ));
+            };
+
+            match token {
+                Token::GroupEnd => break,
+                Token::Comma => continue,
+                Token::Name(name) => {
+                    let mut index = 1;
+                    if tokens.next_if(|(_, t)| *t == Token::OpenParen).is_some() {
+                        index = match tokens.next() {
+                            Some((_, Token::Number(1, i))) if i >= 1. && i.fract() == 0. => {
+                                i as usize
+                            }
+                            _ => {
+                                return Err(ImportError::Syntax(
+                                    line,
+                                    format!("invalid index of \"{name}\""),
+                                ))
+                            }
+                        };
+                        if tokens.next_if(|(_, t)| *t == Token::CloseParen).is_none() {
+                            return Err(ImportError::Syntax(
+                                line,
+                                format!("missing \")\" after index of \"{name}\""),
+                            ));
+                        }
+                    }
+                    if tokens.next_if(|(_, t)| *t == Token::Equals).is_none() {
+                        return Err(ImportError::Syntax(
+                            line,
+                            format!("missing \"=\" after \"{name}\""),
+                        ));
+                    }
+
+                    let mut values = Vec::new();
+                    while let Some((_, token)) = tokens.peek().cloned() {
+                        match token {
+                            Token::Number(repeat, number) => {
+                                values.extend(std::iter::repeat_n(Value::Number(number), repeat))
+                            }
+                            Token::String(str) => values.push(Value::String(str)),
+                            Token::Comma => (),
+                            Token::Name(name)
+                                if logical(&name).is_some() && !is_assignment(&tokens) =>
+                            {
+                                values.push(Value::Logical(logical(&name).unwrap()))
+                            }
+                            _ => break,
+                        }
+                        tokens.next();
+                    }
+
+                    group.assignments.push(Assignment {
+                        name,
+                        index,
+                        values,
+                    });
+                }
+                token => {
+                    return Err(ImportError::Syntax(line, format!("unexpected {token:?}")));
+                }
+            }
+        }
+
+        groups.push(group);
+    }
+
+    Ok(groups)
+}
+
+/// Checks whether the next name token is the start of a new assignment,
+/// instead of a logical value (e.g. a variable named `t`).
+fn is_assignment(tokens: &std::iter::Peekable<std::vec::IntoIter<(usize, Token)>>) -> bool {
+    let mut tokens = tokens.clone();
+    tokens.next();
+    matches!(tokens.next(), Some((_, Token::Equals | Token::OpenParen)))
+}
+
+/// Converts an unquoted logical value.
+fn logical(name: &str) -> Option<bool> {
+    match name {
+        ".true." | ".t." | "t" => Some(true),
+        ".false." | ".f." | "f" => Some(false),
+        _ => None,
+    }
+}
+
+/// Splits the input into tokens, together with their line number.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ImportError> {
+    let mut tokens = Vec::new();
+    let mut in_group = false;
+
+    for (i, line) in input.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.split('!').next().unwrap_or_default();
+        let mut chars = line.char_indices().peekable();
+
+        while let Some((start, c)) = chars.next() {
+            let token = match c {
+                c if c.is_whitespace() => continue,
+                '$' | '&' => {
+                    let mut name = String::new();
+                    while let Some((_, c)) = chars.next_if(|(_, c)| c.is_alphanumeric()) {
+                        name.push(c.to_ascii_lowercase());
+                    }
+                    if name.is_empty() || name == "end" {
+                        in_group = false;
+                        Token::GroupEnd
+                    } else if in_group {
+                        return Err(ImportError::Syntax(
+                            line_number,
+                            format!("group ${name} starts inside another group"),
+                        ));
+                    } else {
+                        in_group = true;
+                        Token::GroupStart(name)
+                    }
+                }
+                // Ignore everything outside of groups
+                _ if !in_group => continue,
+                '/' => {
+                    in_group = false;
+                    Token::GroupEnd
+                }
+                '(' => Token::OpenParen,
+                ')' => Token::CloseParen,
+                '=' => Token::Equals,
+                ',' => Token::Comma,
+                '\'' | '"' => {
+                    let mut str = String::new();
+                    loop {
+                        match chars.next() {
+                            // Two quotes are an escaped quote
+                            Some((_, q)) if q == c && chars.next_if(|(_, q)| *q == c).is_some() => {
+                                str.push(c)
+                            }
+                            Some((_, q)) if q == c => break,
+                            Some((_, q)) => str.push(q),
+                            None => {
+                                return Err(ImportError::Syntax(
+                                    line_number,
+                                    "string is not closed".into(),
+                                ))
+                            }
+                        }
+                    }
+                    Token::String(str)
+                }
+                c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                    let mut end = start + c.len_utf8();
+                    while let Some((i, c)) = chars.next_if(|(i, c)| {
+                        c.is_ascii_alphanumeric()
+                            || *c == '.'
+                            || *c == '*'
+                            || ((*c == '-' || *c == '+')
+                                && line[..*i].ends_with(['e', 'E', 'd', 'D']))
+                    }) {
+                        end = i + c.len_utf8();
+                    }
+                    let word = &line[start..end];
+
+                    if let Some(value) = logical(&word.to_ascii_lowercase()) {
+                        Token::Name(if value { ".true." } else { ".false." }.into())
+                    } else {
+                        parse_number(word).ok_or_else(|| {
+                            ImportError::Syntax(line_number, format!("invalid number \"{word}\""))
+                        })?
+                    }
+                }
+                c if c.is_alphabetic() => {
+                    let mut name = c.to_ascii_lowercase().to_string();
+                    while let Some((_, c)) =
+                        chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_')
+                    {
+                        name.push(c.to_ascii_lowercase());
+                    }
+                    Token::Name(name)
+                }
+                c => {
+                    return Err(ImportError::Syntax(
+                        line_number,
+                        format!("unexpected character \"{c}\""),
+                    ));
+                }
+            };
+
+            tokens.push((line_number, token));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parses a number with an optional repeat count, e.g. `3*1.0D-3`.
+fn parse_number(word: &str) -> Option<Token> {
+    let (repeat, number) = match word.split_once('*') {
+        Some((repeat, number)) => (repeat.parse().ok()?, number),
+        None => (1, word),
+    };
+
+    let number = number.replace(['d', 'D'], "e").parse().ok()?;
+
+    Some(Token::Number(repeat, number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_groups() {
+        let input = "
+            Shuttle ascent
+             $gendat
+              event = 1, critr = 'tdurp', value = 1.5D+1,
+              tvc1(2) = 2*1.0e5, ! comment
+              npc(5) = 2, flag = .true.,
+             $
+             &SEARCH srchm=4 /
+        ";
+
+        let groups = parse(input).unwrap();
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "gendat");
+        assert_eq!(
+            groups[0].assignments,
+            [
+                Assignment {
+                    name: "event".into(),
+                    index: 1,
+                    values: vec![Value::Number(1.)]
+                },
+                Assignment {
+                    name: "critr".into(),
+                    index: 1,
+                    values: vec![Value::String("tdurp".into())]
+                },
+                Assignment {
+                    name: "value".into(),
+                    index: 1,
+                    values: vec![Value::Number(15.)]
+                },
+                Assignment {
+                    name: "tvc1".into(),
+                    index: 2,
+                    values: vec![Value::Number(1e5), Value::Number(1e5)]
+                },
+                Assignment {
+                    name: "npc".into(),
+                    index: 5,
+                    values: vec![Value::Number(2.)]
+                },
+                Assignment {
+                    name: "flag".into(),
+                    index: 1,
+                    values: vec![Value::Logical(true)]
+                },
+            ]
+        );
+        assert_eq!(groups[1].name, "search");
+        assert_eq!(groups[1].assignments[0].name, "srchm");
+    }
+
+    #[test]
+    fn unclosed_group() {
+        parse("$gendat event = 1,").unwrap_err();
+    }
+
+    #[test]
+    fn invalid_number() {
+        parse("$gendat value = 1.0.0 $").unwrap_err();
+    }
+}
@@ -11,17 +11,23 @@
 
 mod atmosphere;
 mod config;
+mod epoch;
 mod example_data;
+mod import;
 mod integration;
+mod orientation;
 mod phase;
 mod planet;
 mod state;
 mod steering;
+mod third_body;
 mod transformations;
 mod utils;
 mod vehicle;
 
 use config::PhaseConfig;
+pub use config::VERSION as CONFIG_VERSION;
+pub use import::{import_deck, ImportError, ImportedDeck};
 use phase::Phase;
 use state::State;
 use std::{error::Error, fs::File, io::BufReader, path::Path};
@@ -39,20 +45,68 @@ impl Simulation {
         let file = File::open(path)?;
         let reader = BufReader::new(file);
 
-        let config = serde_json::from_reader(reader)?;
+        let value = serde_json::from_reader(reader)?;
+        let config = config::from_value(value)?;
 
         Ok(Self { config })
     }
 
+    /// Migrates the configuration file at `path` in place to the current
+    /// version of the configuration format. Returns the original version, or
+    /// `None` if the file already has the current version.
+    pub fn migrate_file<P: AsRef<Path>>(path: P) -> Result<Option<u64>, Box<dyn Error>> {
+        let file = File::open(&path)?;
+        let reader = BufReader::new(file);
+
+        let value = serde_json::from_reader(reader)?;
+        let migrated = config::migrate(value)?;
+        if migrated.version == config::VERSION {
+            return Ok(None);
+        }
+
+        // Make sure the migrated file is valid before overwriting it
+        config::from_value(migrated.value.clone())?;
+        std::fs::write(path, serde_json::to_string_pretty(&migrated.value)? + "\n")?;
+
+        Ok(Some(migrated.version))
+    }
+
+    /// Returns the JSON Schema of the configuration file. It can be used by
+    /// editors to validate and autocomplete configuration files.
+    pub fn schema() -> serde_json::Value {
+        config::schema()
+    }
+
+    /// Builds each phase without running the simulation, and returns the
+    /// effective configuration of each phase after inheriting the
+    /// configuration of the previous phase (see [`Phase::resolved`]).
+    ///
+    /// The first steering coefficient of each axis is omitted, as it is only
+    /// known when the simulation is running.
+    pub fn resolve(&self) -> serde_json::Value {
+        let mut resolved = Vec::new();
+        let mut prev_phase = None;
+        for config in self.config.iter() {
+            let phase = Phase::new(prev_phase.as_ref(), config);
+            resolved.push(
+                serde_json::to_value(phase.resolved())
+                    .expect("the phase configuration should always be serializable"),
+            );
+            prev_phase = Some(phase);
+        }
+
+        serde_json::Value::Array(resolved)
+    }
+
     /// Runs the simulation. It will create each phase from the previous phase
     /// and its configuration with [`Phase::new`], reset it with
     /// [`Phase::reset`] and run it with [`Phase::run`].
     pub fn run(&self) -> State {
         let mut prev_phase = None;
         let mut phase = Phase::default();
-        for (i, config) in self.config.iter().enumerate() {
-            println!("Starting Phase {}", i + 1);
+        for config in self.config.iter() {
             phase = Phase::new(prev_phase, config);
+            println!("Starting Phase {}", phase.name);
             phase.reset();
 
             phase.run();
@@ -77,7 +131,8 @@ mod tests {
 
         let str = include_str!("../../utils/example.json");
 
-        let config: Vec<PhaseConfig> = serde_json::from_str(str).unwrap();
+        let config: Vec<PhaseConfig> =
+            config::from_value(serde_json::from_str(str).unwrap()).unwrap();
 
         let sim = Simulation { config };
         let state = sim.run();
@@ -86,4 +141,22 @@ mod tests {
         assert_almost_eq_rel!(state.velocity.norm(), TARGET_VEL, 0.00003);
         assert_almost_eq_rel!(state.time, DATA_POINTS[3].time, 0.003);
     }
+
+    #[test]
+    fn resolve() {
+        let str = include_str!("../../utils/example.json");
+
+        let mut config: Vec<PhaseConfig> =
+            config::from_value(serde_json::from_str(str).unwrap()).unwrap();
+        config[8].name = Some("Second stage".into());
+
+        let sim = Simulation { config };
+        let resolved = sim.resolve();
+
+        assert_eq!(resolved[7]["name"], "8");
+        assert_eq!(resolved[7]["vehicle"]["engines"], serde_json::json!([]));
+        assert_eq!(resolved[8]["name"], "Second stage");
+        assert_eq!(resolved[9]["stepsize"], 20.);
+        assert_eq!(resolved[9]["max_acceleration"], 29.41995);
+    }
 }
@@ -0,0 +1,205 @@
+//! Defines the [`PlanetOrientation`], which determines the orientation of the
+//! planet frame relative to the inertial frame.
+//!
+//! The inertial frame is the mean equator and equinox of J2000 (which is
+//! equal to the GCRF within a few tens of milliarcseconds). For the Earth,
+//! the planet frame is rotated by the Greenwich sidereal time and optionally
+//! by the precession and nutation of the Earth's axis, see Montenbruck and
+//! Gill: "Satellite Orbits", p. 172 ff.
+//!
+//! Precession uses the IAU 1976 model. Nutation uses the four largest terms
+//! of the IAU 1980 model, which is accurate to about 0.5", see Meeus:
+//! "Astronomical Algorithms", p. 144.
+
+use crate::epoch::tt_to_utc;
+use crate::utils::constants::{
+    ARCSECONDS_PER_DEGREE, DAYS_PER_JULIAN_CENTURY, JULIAN_DATE_J2000, OBLIQUITY_J2000,
+    SECONDS_PER_DAY,
+};
+use nalgebra::{Rotation3, Vector3};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Represents the model of the orientation of the planet frame.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanetOrientation {
+    /// The planet frame is aligned with the inertial frame at simulation time
+    /// 0 and rotates about the z-axis. The epoch is ignored, so the inertial
+    /// frame is not the J2000 frame.
+    #[default]
+    Aligned,
+    /// The planet frame is rotated by the Greenwich mean sidereal time at the
+    /// epoch and rotates about the z-axis. Precession and nutation are
+    /// neglected. Only valid for the Earth.
+    SiderealTime,
+    /// The planet frame is rotated by the Greenwich apparent sidereal time, as
+    /// well as by precession and nutation. Only valid for the Earth.
+    PrecessionNutation,
+}
+
+impl PlanetOrientation {
+    /// Calculates the transformation matrix from the inertial frame to the
+    /// planet frame.
+    ///
+    /// Uses the epoch as Julian date (TT), the simulation time in sec and the
+    /// planet's rotation rate in rad/sec.
+    pub fn inertial_to_planet(&self, epoch: f64, time: f64, rotation_rate: f64) -> Rotation3<f64> {
+        match self {
+            PlanetOrientation::Aligned => rotation_z(rotation_rate * time),
+            PlanetOrientation::SiderealTime => {
+                rotation_z(greenwich_mean_sidereal_time(tt_to_utc(epoch)) + rotation_rate * time)
+            }
+            PlanetOrientation::PrecessionNutation => {
+                let julian_date = epoch + time / SECONDS_PER_DAY;
+                let (nutation, equation_of_equinoxes) = nutation(julian_date);
+
+                rotation_z(
+                    greenwich_mean_sidereal_time(tt_to_utc(epoch))
+                        + rotation_rate * time
+                        + equation_of_equinoxes,
+                ) * nutation
+                    * precession(julian_date)
+            }
+        }
+    }
+}
+
+/// Calculates the transformation matrix of a frame rotated by the angle in rad
+/// about the z-axis.
+fn rotation_z(angle: f64) -> Rotation3<f64> {
+    Rotation3::from_axis_angle(&Vector3::z_axis(), -angle)
+}
+
+/// Calculates the Greenwich mean sidereal time in rad at the Julian date (UT1),
+/// see Meeus: "Astronomical Algorithms", p. 88.
+pub fn greenwich_mean_sidereal_time(julian_date: f64) -> f64 {
+    let days = julian_date - JULIAN_DATE_J2000;
+    let t = days / DAYS_PER_JULIAN_CENTURY;
+
+    let gmst =
+        280.46061837 + 360.98564736629 * days + 0.000387933 * t.powi(2) - t.powi(3) / 38710000.;
+
+    gmst.rem_euclid(360.).to_radians()
+}
+
+/// Calculates the precession matrix from the mean equator and equinox of J2000
+/// to the mean equator and equinox at the Julian date (TT).
+fn precession(julian_date: f64) -> Rotation3<f64> {
+    let t = (julian_date - JULIAN_DATE_J2000) / DAYS_PER_JULIAN_CENTURY;
+
+    let zeta = (2306.2181 * t + 0.30188 * t.powi(2) + 0.017998 * t.powi(3)) / ARCSECONDS_PER_DEGREE;
+    let z = (2306.2181 * t + 1.09468 * t.powi(2) + 0.018203 * t.powi(3)) / ARCSECONDS_PER_DEGREE;
+    let theta =
+        (2004.3109 * t - 0.42665 * t.powi(2) - 0.041833 * t.powi(3)) / ARCSECONDS_PER_DEGREE;
+
+    rotation_z(-z.to_radians())
+        * Rotation3::from_axis_angle(&Vector3::y_axis(), -theta.to_radians())
+        * rotation_z(-zeta.to_radians())
+}
+
+/// Calculates the nutation matrix from the mean to the true equator and
+/// equinox at the Julian date (TT), as well as the equation of the equinoxes
+/// in rad.
+fn nutation(julian_date: f64) -> (Rotation3<f64>, f64) {
+    let t = (julian_date - JULIAN_DATE_J2000) / DAYS_PER_JULIAN_CENTURY;
+
+    // Longitude of the ascending node of the Moon
+    let omega = (125.04452 - 1934.136261 * t).to_radians();
+    // Mean longitudes of the Sun and the Moon
+    let l_sun = (280.4665 + 36000.7698 * t).to_radians();
+    let l_moon = (218.3165 + 481267.8813 * t).to_radians();
+
+    let nutation_longitude =
+        (-17.20 * omega.sin() - 1.32 * (2. * l_sun).sin() - 0.23 * (2. * l_moon).sin()
+            + 0.21 * (2. * omega).sin())
+            / ARCSECONDS_PER_DEGREE;
+    let nutation_obliquity =
+        (9.20 * omega.cos() + 0.57 * (2. * l_sun).cos() + 0.10 * (2. * l_moon).cos()
+            - 0.09 * (2. * omega).cos())
+            / ARCSECONDS_PER_DEGREE;
+
+    let mean_obliquity = OBLIQUITY_J2000 - 0.0130042 * t;
+
+    let nutation = Rotation3::from_axis_angle(
+        &Vector3::x_axis(),
+        (mean_obliquity + nutation_obliquity).to_radians(),
+    ) * rotation_z(-nutation_longitude.to_radians())
+        * Rotation3::from_axis_angle(&Vector3::x_axis(), -mean_obliquity.to_radians());
+
+    (
+        nutation,
+        (nutation_longitude * mean_obliquity.to_radians().cos()).to_radians(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_almost_eq_rel;
+
+    #[test]
+    fn sidereal_time() {
+        // 1987-04-10 19:21:00 UT, see Meeus: "Astronomical Algorithms",
+        // ex. 12.b: 8h 34m 57.0896s
+        let gmst = greenwich_mean_sidereal_time(2446896.30625);
+        assert_almost_eq_rel!(
+            gmst.to_degrees(),
+            (8. + 34. / 60. + 57.0896 / 3600.) * 15_f64,
+            1e-8
+        );
+
+        // The planet frame is aligned at time 0
+        assert_eq!(
+            PlanetOrientation::Aligned.inertial_to_planet(2446896.30625, 0., 7.29211e-5),
+            Rotation3::identity()
+        );
+
+        // The x-axis of the planet frame points to the Greenwich meridian
+        let epoch = 2446896.30625 + 55.184 / SECONDS_PER_DAY;
+        let inertial_to_planet =
+            PlanetOrientation::SiderealTime.inertial_to_planet(epoch, 0., 7.29211e-5);
+        let greenwich = inertial_to_planet.inverse_transform_vector(&Vector3::x());
+        assert_almost_eq_rel!(
+            greenwich.y.atan2(greenwich.x).to_degrees(),
+            gmst.to_degrees(),
+            1e-9
+        );
+    }
+
+    #[test]
+    fn precession_nutation() {
+        // 1987-04-10 0:00 TD, see Meeus: "Astronomical Algorithms", ex. 22.a:
+        // nutation in longitude -3.788", in obliquity 9.443", true obliquity
+        // 23°26'36.850"
+        let julian_date = 2446895.5;
+        let (nutation, equation_of_equinoxes) = nutation(julian_date);
+
+        // The ecliptic pole is not affected by the nutation in longitude
+        let true_obliquity = (23. + 26. / 60. + 36.850 / 3600.) * 1_f64;
+        let mean_obliquity = true_obliquity - 9.443 / 3600.;
+        let pole = Rotation3::from_axis_angle(&Vector3::x_axis(), mean_obliquity.to_radians())
+            * Vector3::z();
+        let true_pole = nutation * pole;
+        assert_almost_eq_rel!(
+            true_pole.z.acos().to_degrees(),
+            true_obliquity,
+            0.5 / 3600. / true_obliquity
+        );
+        assert_almost_eq_rel!(
+            equation_of_equinoxes.to_degrees() * 3600.,
+            -3.788 * true_obliquity.to_radians().cos(),
+            0.15
+        );
+
+        // The pole moves by theta = 2004.31" per century due to precession
+        let pole = precession(JULIAN_DATE_J2000 + DAYS_PER_JULIAN_CENTURY)
+            .inverse_transform_vector(&Vector3::z());
+        assert_almost_eq_rel!(
+            pole.z.acos().to_degrees() * 3600.,
+            2004.3109 - 0.42665 - 0.041833_f64,
+            1e-9
+        );
+        assert_eq!(precession(JULIAN_DATE_J2000), Rotation3::identity());
+    }
+}
@@ -11,16 +11,28 @@
 use crate::atmosphere::Atmosphere;
 use crate::config::{InitConfig, PhaseConfig};
 use crate::integration::Integrator;
-use crate::planet::Planet;
+use crate::orientation::PlanetOrientation;
+use crate::planet::{Planet, Terrain};
 use crate::state::{State, StateVariable};
 use crate::steering::Steering;
-use crate::transformations::{inertial_to_body, inertial_to_planet};
-use crate::vehicle::Vehicle;
-use nalgebra::{vector, Vector3};
+use crate::third_body::ThirdBody;
+use crate::transformations::{inertial_to_body, inertial_to_launch};
+use crate::utils::constants::{JULIAN_DATE_J2000, NEARLY_ZERO, SECONDS_PER_DAY};
+use crate::vehicle::{Burnout, Throttle, ThrottleLimits, Vehicle};
+use nalgebra::{Rotation3, Vector3};
+use serde::Serialize;
+
+/// The time constant in sec, with which the dynamic pressure approaches its
+/// maximum.
+const DYNAMIC_PRESSURE_TIME_CONSTANT: f64 = 1.;
 
 /// Represents a phase.
 #[derive(Debug, Clone)]
 pub struct Phase {
+    /// The number of the phase. The default phase has the number 0.
+    index: usize,
+    /// The name of the phase, used in the output. Defaults to the phase number.
+    pub name: String,
     /// The current state.
     pub state: State,
     /// The vehicle which is simulated.
@@ -31,13 +43,24 @@ pub struct Phase {
     atmosphere: Atmosphere,
     /// The attracting planet.
     planet: Planet,
-    /// Defines the launch frame. The array consists of the geocentric
-    /// latitude, longitude and azimuth in rad.
-    launch: [f64; 3],
+    /// The celestial bodies perturbing the trajectory.
+    third_bodies: Vec<ThirdBody>,
+    /// The terrain elevation of the planet.
+    terrain: Option<Terrain>,
+    /// The Julian date (TT) at simulation time 0.
+    epoch: f64,
+    /// The model of the orientation of the planet frame.
+    orientation: PlanetOrientation,
+    /// Defines the launch frame as transformation from the inertial frame.
+    inertial_to_launch: Rotation3<f64>,
     /// The integrator used to integrate the equations of motion.
     integrator: Integrator,
     /// The specified maximum acceleration allowed in m/s^2.
     max_acceleration: f64,
+    /// The commanded throttle, limited by the maximum acceleration.
+    throttle: Throttle,
+    /// The additional limits of the throttle.
+    throttle_limits: ThrottleLimits,
     /// The step size of the current time step in sec.
     /// It is adjusted at the end of a phase to satisfy the end criterion.
     stepsize: f64,
@@ -47,10 +70,44 @@ pub struct Phase {
     end_criterion: (StateVariable, f64),
     /// The number of tries to hit the target value.
     end_criterion_tries: usize,
+    /// Whether the engines were cut off, as the propellant is depleted.
+    cutoff: bool,
     /// Whether the current phase has ended.
     pub ended: bool,
 }
 
+/// The effective configuration of a phase, after it inherited the
+/// configuration of the previous phase. See [`Phase::resolved`].
+#[derive(Debug, Serialize)]
+pub struct ResolvedPhase<'a> {
+    /// The name of the phase.
+    name: &'a str,
+    /// The vehicle which is simulated.
+    vehicle: &'a Vehicle,
+    /// The steering of the vehicle.
+    steering: &'a Steering,
+    /// The atmosphere of the planet.
+    atmosphere: &'a Atmosphere,
+    /// The attracting planet.
+    planet: &'a Planet,
+    /// The celestial bodies perturbing the trajectory.
+    third_bodies: &'a [ThirdBody],
+    /// The terrain elevation of the planet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    terrain: &'a Option<Terrain>,
+    /// The specified maximum acceleration allowed in m/s^2. `None` if
+    /// disabled.
+    max_acceleration: Option<f64>,
+    /// The commanded throttle.
+    throttle: &'a Throttle,
+    /// The additional limits of the throttle.
+    throttle_limits: &'a ThrottleLimits,
+    /// The specified time step size in sec.
+    stepsize: f64,
+    /// The variable and its target value to end the phase.
+    end_criterion: (StateVariable, f64),
+}
+
 impl Phase {
     /// Represents the equations of motion. The input is a state where only the
     /// primary state (set by the integrator) is set. This function will slowly
@@ -69,42 +126,59 @@ impl Phase {
         // Faulty order will not raise warnings!
 
         // Additional primary state values
-        let inertial_to_planet = inertial_to_planet(state.time, self.planet.rotation_rate);
-        state.position_planet = inertial_to_planet.transform_vector(&state.position);
-        state.velocity_planet = self.planet.velocity_planet(state.position, state.velocity);
-        state.altitude = self.planet.altitude(state.position);
-        state.altitude_geopotential = self.planet.geopotential_altitude(state.position);
-        state.velocity_planet = self.planet.velocity_planet(state.position, state.velocity);
+        let inertial_to_planet =
+            self.orientation
+                .inertial_to_planet(self.epoch, state.time, self.planet.rotation_rate);
+        self.position_values(&mut state, &inertial_to_planet);
+        state.velocity_planet =
+            self.planet
+                .velocity_planet(state.position, state.velocity, &inertial_to_planet);
         state.propellant_mass = state.mass - self.vehicle.structure_mass;
+        state.time_since_ignition = self.vehicle.time_since_ignition(&state);
 
         // Gravity acceleration
-        state.gravity_acceleration = self.planet.gravity(state.position);
+        state.gravity_acceleration = self.planet.gravity(state.position, &inertial_to_planet);
+        let julian_date = self.epoch + state.time / SECONDS_PER_DAY;
+        for body in self.third_bodies.iter() {
+            state.gravity_acceleration += body.acceleration(state.position, julian_date);
+        }
 
         // Atmospheric data
-        state.velocity_atmosphere = self.atmosphere.velocity_atmosphere(&state);
+        state.velocity_atmosphere = self
+            .atmosphere
+            .velocity_atmosphere(&state, &inertial_to_planet);
         state.temperature = self.atmosphere.temperature(&state);
         state.pressure = self.atmosphere.pressure(&state);
         state.density = self.atmosphere.density(&state);
         state.mach_number = self.atmosphere.mach_number(&state);
         state.dynamic_pressure = self.atmosphere.dynamic_pressure(&state);
+        state.viscosity = self.atmosphere.viscosity(&state);
+        state.unit_reynolds_number = self.atmosphere.unit_reynolds_number(&state);
+        state.reynolds_number = self.vehicle.reynolds_number(&state);
+        state.mean_free_path = self.atmosphere.mean_free_path(&state);
+        state.knudsen_number = self.vehicle.knudsen_number(&state);
 
         // Attitude
         state.euler_angles = self.steering.euler_angles(&state);
-        let inertial_to_body = inertial_to_body(self.launch, state.euler_angles);
+        let inertial_to_body = inertial_to_body(&self.inertial_to_launch, state.euler_angles);
 
         // Aerodynamic acceleration
         state.alpha = Vehicle::alpha(inertial_to_body.transform_vector(&state.velocity_atmosphere));
         state.aero_force_body = self.vehicle.aero_force(&state);
 
         // Thrust acceleration
-        state.throttle = self.vehicle.auto_throttle(
-            self.max_acceleration,
-            state.mass,
-            state.pressure,
-            state.aero_force_body,
-        );
-        state.thrust_force_body = self.vehicle.thrust_force(state.throttle, state.pressure);
-        state.massflow = self.vehicle.massflow(state.throttle);
+        state.throttle = if self.cutoff {
+            0.
+        } else {
+            let max_throttle = self
+                .throttle
+                .at_state(&state)
+                .min(self.throttle_limit(&state, &inertial_to_body));
+            self.vehicle
+                .auto_throttle(self.max_acceleration, max_throttle, &state)
+        };
+        state.thrust_force_body = self.vehicle.thrust_force(state.throttle, &state);
+        state.massflow = self.vehicle.massflow(state.throttle, &state);
 
         // Vehicle acceleration
         state.vehicle_acceleration_body =
@@ -113,7 +187,10 @@ impl Phase {
             || state.throttle.is_nan()
         {
             // Intersection would require negative thrust
-            panic!("Could not stay in max. acceleration (check aero forces)")
+            panic!(
+                "Phase {}: Could not stay in max. acceleration (check aero forces)",
+                self.name
+            )
         }
 
         // Acceleration
@@ -125,14 +202,95 @@ impl Phase {
         state
     }
 
-    /// Estimates the time until the target value is reached.
+    /// Calculates the values, which only depend on the position, i.e. the
+    /// position in the planet frame and the altitudes.
+    fn position_values(&self, state: &mut State, inertial_to_planet: &Rotation3<f64>) {
+        state.position_planet = inertial_to_planet.transform_vector(&state.position);
+        state.altitude = self.planet.altitude(state.position_planet);
+        state.altitude_geopotential = self.planet.geopotential_altitude(state.position_planet);
+        (
+            state.latitude_geodetic,
+            state.longitude,
+            state.altitude_geodetic,
+        ) = self.planet.geodetic(state.position_planet);
+        state.altitude_above_ground = match &self.terrain {
+            Some(terrain) => {
+                state.altitude_geodetic
+                    - terrain.elevation(state.latitude_geodetic, state.longitude)
+            }
+            None => state.altitude_geodetic,
+        };
+    }
+
+    /// Calculates the maximum throttle allowed by the [`ThrottleLimits`]. Each
+    /// limit is solved for the throttle, as the thrust is linear in it.
+    ///
+    /// For the dynamic pressure, the throttle is chosen so that its rate of
+    /// change is at most `(q_max - q) / tau`. The rate of change consists of
+    /// the acceleration along the velocity with respect to the atmosphere and
+    /// the change of the density with the altitude. The density gradient is
+    /// calculated with a finite difference.
+    ///
+    /// Requires the state to be calculated up to the aerodynamic force.
+    fn throttle_limit(&self, state: &State, inertial_to_body: &Rotation3<f64>) -> f64 {
+        let mut limit = f64::INFINITY;
+
+        if let Some(max_dynamic_pressure) = self.throttle_limits.max_dynamic_pressure {
+            // The atmosphere may be given over any of the altitudes, so all of
+            // them are recalculated 1 m above the vehicle
+            let mut above = state.clone();
+            above.position += state.position.normalize();
+            let inertial_to_planet = self.orientation.inertial_to_planet(
+                self.epoch,
+                state.time,
+                self.planet.rotation_rate,
+            );
+            self.position_values(&mut above, &inertial_to_planet);
+            let density_gradient = self.atmosphere.density(&above) - state.density;
+            let climb_rate = state.position.normalize().dot(&state.velocity_planet);
+
+            let velocity = state.velocity_atmosphere;
+            let aero = inertial_to_body.inverse_transform_vector(&state.aero_force_body);
+            let max_thrust =
+                inertial_to_body.inverse_transform_vector(&self.vehicle.thrust_force(1., state));
+
+            // The rate of change of the dynamic pressure is `a + b * throttle`
+            let a = state.density * velocity.dot(&(aero / state.mass + state.gravity_acceleration))
+                + 0.5 * velocity.norm_squared() * density_gradient * climb_rate;
+            let b = state.density * velocity.dot(&max_thrust) / state.mass;
+            let max_rate =
+                (max_dynamic_pressure - state.dynamic_pressure) / DYNAMIC_PRESSURE_TIME_CONSTANT;
+
+            if b > 0. {
+                limit = limit.min(((max_rate - a) / b).max(0.));
+            }
+        }
+        if let Some(max_axial_acceleration) = self.throttle_limits.max_axial_acceleration {
+            limit = limit.min(self.vehicle.axial_throttle(max_axial_acceleration, state));
+        }
+        if let Some(thrust_to_weight) = self.throttle_limits.thrust_to_weight {
+            limit = limit.min(
+                self.vehicle
+                    .thrust_to_weight_throttle(thrust_to_weight, state),
+            );
+        }
+
+        limit
+    }
+
+    /// Estimates the time until the target value of the criterion is reached.
     ///
     /// It does this by estimating the derivative of the cost function (meaning
     /// the difference between current and target value) using the change over
     /// the last timestep.
-    fn time_to_go(&self, old_state: &State, new_state: &State) -> f64 {
-        let y_t = self.end_criterion.0.get_value(old_state) - self.end_criterion.1;
-        let y_t_1 = self.end_criterion.0.get_value(new_state) - self.end_criterion.1;
+    fn time_to_go(
+        &self,
+        criterion: (StateVariable, f64),
+        old_state: &State,
+        new_state: &State,
+    ) -> f64 {
+        let y_t = criterion.0.get_value(old_state) - criterion.1;
+        let y_t_1 = criterion.0.get_value(new_state) - criterion.1;
         let dt = self.stepsize;
 
         -y_t * dt / (y_t_1 - y_t)
@@ -163,15 +321,47 @@ impl Phase {
     /// size is calculated with [`Phase::time_to_go`].
     /// Otherwise it will simply do another time step until one of the above
     /// occurs.
+    ///
+    /// The propellant depletion of a stage is found the same way, after which
+    /// the stage is separated with [`Phase::burnout`]. Also, the step ends at
+    /// the burn time of an engine, which is then burned out.
     pub fn step(&mut self) {
         if self.ended {
-            panic!("Phase already has ended")
+            panic!("Phase {} already has ended", self.name)
         }
 
         let state = self
             .integrator
             .step(|state| self.system(state), &self.state, self.stepsize);
 
+        if !self.cutoff && state.massflow < 0. && state.propellant_mass < -1e-3 {
+            // The propellant was depleted within the time step, try again.
+            if self.end_criterion_tries > 20 {
+                panic!(
+                    "Phase {}: Could not find depletion of propellant",
+                    self.name
+                )
+            }
+
+            self.stepsize =
+                self.time_to_go((StateVariable::PropellantMass, 0.), &self.state, &state);
+            self.end_criterion_tries += 1;
+            return;
+        }
+
+        if let Some(burn_time_to_go) = self.vehicle.burn_time_to_go(&self.state) {
+            let burn_time = self.state.time_since_ignition + burn_time_to_go;
+            if state.time_since_ignition > burn_time + NEARLY_ZERO {
+                // An engine burned out within the time step, try again.
+                self.stepsize = self.time_to_go(
+                    (StateVariable::TimeSinceIgnition, burn_time),
+                    &self.state,
+                    &state,
+                );
+                return;
+            }
+        }
+
         if (self.end_criterion.0.get_value(&state) - self.end_criterion.1).abs() < 1e-3 {
             // We found a good last stepsize. Phase has ended.
             self.ended = true;
@@ -179,15 +369,49 @@ impl Phase {
         } else if self.event_is_active(&self.state, &state) {
             // The stepsize was too big, try again.
             if self.end_criterion_tries > 20 {
-                panic!("Could not find zero crossing of event")
+                panic!("Phase {}: Could not find zero crossing of event", self.name)
             }
 
-            self.stepsize = self.time_to_go(&self.state, &state);
+            self.stepsize = self.time_to_go(self.end_criterion, &self.state, &state);
             self.end_criterion_tries += 1;
         } else {
             // Normal step, still more steps to go.
             self.state = state;
         }
+
+        if self.vehicle.burn_out(&self.state) {
+            // The thrust of the burned out engines is removed from the state
+            self.state = self.system(self.state.clone());
+            self.stepsize = self.base_stepsize;
+        }
+        if !self.cutoff && self.state.massflow < 0. && self.state.propellant_mass < 1e-3 {
+            self.burnout();
+        }
+    }
+
+    /// Handles the depletion of the propellant.
+    ///
+    /// If there is a stage to separate, it is separated, the mass is set to
+    /// the mass of the remaining stages and its engines are ignited.
+    /// Otherwise, the engines are cut off, or the simulation panics if
+    /// [`Burnout::Error`] is configured and the phase did not end with the
+    /// burnout. Then the full state is recalculated, and the step size and
+    /// the tries to reach the end criterion are reset.
+    fn burnout(&mut self) {
+        if let Some(mass) = self.vehicle.separate_stage() {
+            self.state.mass = mass;
+            self.vehicle.ignite(self.state.time);
+        } else if self.vehicle.burnout == Burnout::Error && !self.ended {
+            panic!(
+                "Phase {}: Propellant depleted at {:.3} sec",
+                self.name, self.state.time
+            )
+        } else {
+            self.cutoff = true;
+        }
+        self.state = self.system(self.state.clone());
+        self.stepsize = self.base_stepsize;
+        self.end_criterion_tries = 0;
     }
 
     /// Runs the Phase.
@@ -217,18 +441,27 @@ impl Default for Phase {
     /// phase and overwrite its parameters with its configuration.
     fn default() -> Self {
         Self {
+            index: 0,
+            name: "0".into(),
             state: State::default(),
             vehicle: Vehicle::default(),
             max_acceleration: f64::INFINITY,
+            throttle: Throttle::default(),
+            throttle_limits: ThrottleLimits::default(),
             steering: Steering::default(),
             planet: Planet::default(),
-            launch: [0., 0., 0.],
+            third_bodies: Vec::new(),
+            terrain: None,
+            epoch: JULIAN_DATE_J2000,
+            orientation: PlanetOrientation::Aligned,
+            inertial_to_launch: inertial_to_launch(0., 0., 0.),
             atmosphere: Atmosphere::default(),
             integrator: Integrator::RK4,
             stepsize: 1.,
             base_stepsize: 1.,
             end_criterion: (StateVariable::TimeSinceEvent, 0.),
             end_criterion_tries: 0,
+            cutoff: false,
             ended: false,
         }
     }
@@ -243,12 +476,25 @@ impl Phase {
             phase = Self::default();
         }
 
+        phase.index += 1;
+        phase.name = match &config.name {
+            Some(name) => name.clone(),
+            None => phase.index.to_string(),
+        };
+
         // Update previous phase with values from config
         if let Some(config) = &config.planet_model {
             phase.planet = Planet::update_with_config(config);
+            phase.atmosphere.update_with_planet(&phase.planet);
         }
         if let Some(config) = &config.atmosphere {
-            phase.atmosphere.update_with_config(config);
+            phase.atmosphere.update_with_config(config, &phase.planet);
+        }
+        if let Some(config) = &config.third_bodies {
+            phase.third_bodies = config.clone();
+        }
+        if let Some(config) = &config.terrain {
+            phase.terrain = Some(config.clone());
         }
         if let Some(config) = &config.vehicle {
             phase.vehicle.update_with_config(config);
@@ -261,7 +507,25 @@ impl Phase {
             if let Some(config) = config.propellant_mass {
                 phase.state.mass = phase.vehicle.structure_mass + config;
             }
+            // If stages were defined, the vehicle consists of all stages
+            if config
+                .stages
+                .as_ref()
+                .is_some_and(|stages| !stages.is_empty())
+            {
+                phase.state.mass = phase.vehicle.initial_mass();
+            }
+            // The configuration is validated, but the last stage may already
+            // have been separated on the depletion of its propellant
+            if config.separate_stage == Some(true) {
+                if let Some(mass) = phase.vehicle.separate_stage() {
+                    phase.state.mass = mass;
+                }
+            }
         }
+        // The engines are cut off until there is propellant again
+        phase.cutoff = phase.state.mass - phase.vehicle.structure_mass < 1e-3;
+
         if let Some(config) = config.max_acceleration {
             if config == -1. {
                 phase.max_acceleration = f64::INFINITY;
@@ -269,10 +533,17 @@ impl Phase {
                 phase.max_acceleration = config;
             }
         }
+        if let Some(config) = &config.throttle {
+            phase.throttle = config.clone();
+        }
+        if let Some(config) = config.throttle_limits {
+            phase.throttle_limits = config;
+        }
         if let Some(config) = &config.steering {
             phase.steering.update_with_config(config);
         }
         if let Some(config) = config.stepsize {
+            phase.base_stepsize = config;
             phase.stepsize = config;
         }
         if let Some(config) = config.end_criterion {
@@ -286,10 +557,30 @@ impl Phase {
                 .expect("First phase must include init config");
             phase.init(config);
         }
+        // Newly ignited engines start their thrust curves now
+        phase.vehicle.ignite(phase.state.time);
 
         phase
     }
 
+    /// Returns the effective configuration of the phase.
+    pub fn resolved(&self) -> ResolvedPhase<'_> {
+        ResolvedPhase {
+            name: &self.name,
+            vehicle: &self.vehicle,
+            steering: &self.steering,
+            atmosphere: &self.atmosphere,
+            planet: &self.planet,
+            third_bodies: &self.third_bodies,
+            terrain: &self.terrain,
+            max_acceleration: Some(self.max_acceleration).filter(|i| i.is_finite()),
+            throttle: &self.throttle,
+            throttle_limits: &self.throttle_limits,
+            stepsize: self.base_stepsize,
+            end_criterion: self.end_criterion,
+        }
+    }
+
     /// Reset the phase.
     ///
     /// It will set the time since last event to zero, initialize the steering
@@ -315,25 +606,36 @@ impl Phase {
             config.azimuth.to_radians(),
         );
 
-        let k = (self.planet.equatorial_radius / self.planet.polar_radius).powi(2);
-
-        let geocentric_lat = f64::atan(k.powi(2) * lat.tan());
-
-        self.launch = [geocentric_lat, long, az];
-
-        let distance_to_surface =
-            self.planet.equatorial_radius / f64::sqrt(1. + (k - 1.) * geocentric_lat.sin().powi(2));
-
-        self.state.position = distance_to_surface
-            * vector![
-                geocentric_lat.cos() * long.cos(),
-                geocentric_lat.cos() * long.sin(),
-                geocentric_lat.sin()
-            ];
+        if let Some(epoch) = config.epoch {
+            self.epoch = epoch.0;
+            self.atmosphere.update_with_epoch(self.epoch);
+        }
+        // The sidereal time ties the planet frame to J2000 at the epoch
+        if let Some(orientation) = config.orientation {
+            self.orientation = orientation;
+        } else if config.epoch.is_some() {
+            self.orientation = PlanetOrientation::SiderealTime;
+        }
+        let inertial_to_planet =
+            self.orientation
+                .inertial_to_planet(self.epoch, 0., self.planet.rotation_rate);
+
+        // The launch frame is defined at the surface
+        let surface = self.planet.position_from_geodetic(lat, long, 0.);
+        let geocentric_lat = f64::atan2(surface.z, surface.xy().norm());
+        self.inertial_to_launch = inertial_to_launch(geocentric_lat, long, az) * inertial_to_planet;
+
+        self.state.position = inertial_to_planet.inverse_transform_vector(
+            &self
+                .planet
+                .position_from_geodetic(lat, long, config.altitude),
+        );
 
-        self.state.velocity = -self
-            .planet
-            .velocity_planet(self.state.position, Vector3::zeros());
+        self.state.velocity = -self.planet.velocity_planet(
+            self.state.position,
+            Vector3::zeros(),
+            &inertial_to_planet,
+        );
 
         self
     }
@@ -343,14 +645,16 @@ impl Phase {
 mod tests {
     use super::*;
     use crate::assert_almost_eq_rel;
-    use crate::config::SteeringConfig;
+    use crate::config::{self, SteeringConfig};
     use crate::example_data::DATA_POINTS;
+    use crate::utils::constants::STD_GRAVITY;
 
     #[test]
     fn phase_1() {
         let str = include_str!("../../utils/example.json");
 
-        let configs: Vec<PhaseConfig> = serde_json::from_str(str).unwrap();
+        let configs: Vec<PhaseConfig> =
+            config::from_value(serde_json::from_str(str).unwrap()).unwrap();
 
         let mut phase = Phase::new(None, &configs[0]);
 
@@ -370,7 +674,8 @@ mod tests {
     fn phase_11() {
         let str = include_str!("../../utils/example.json");
 
-        let configs: Vec<PhaseConfig> = serde_json::from_str(str).unwrap();
+        let configs: Vec<PhaseConfig> =
+            config::from_value(serde_json::from_str(str).unwrap()).unwrap();
 
         // Cycle through phases to finally build the last one
         let mut phase = Phase::new(None, &configs[0]);
@@ -405,4 +710,274 @@ mod tests {
         assert_almost_eq_rel!(vec phase.state.position, DATA_POINTS[3].position, 0.001);
         assert_almost_eq_rel!(vec phase.state.velocity, DATA_POINTS[3].velocity, 0.001);
     }
+
+    #[test]
+    fn orientation() {
+        let phase = |orientation: &str| {
+            let config: PhaseConfig = serde_json::from_value(serde_json::json!({
+                "planet_model": "smithsonian",
+                "init": {
+                    "latitude": 45, "longitude": 30, "azimuth": 90, "altitude": 1e5,
+                    "epoch": "2025-01-01T00:00:00Z", "orientation": orientation
+                },
+                "end_criterion": ["time", 1]
+            }))
+            .unwrap();
+            let phase = Phase::new(None, &config);
+            let state = phase.system(phase.state.clone());
+            let inertial_to_planet =
+                phase
+                    .orientation
+                    .inertial_to_planet(phase.epoch, 0., phase.planet.rotation_rate);
+            (state, inertial_to_planet)
+        };
+
+        // Defaults to the sidereal time with an epoch
+        let config: PhaseConfig = serde_json::from_value(serde_json::json!({
+            "init": {
+                "latitude": 45, "longitude": 30, "azimuth": 90, "altitude": 0,
+                "epoch": "2025-01-01T00:00:00Z"
+            },
+            "end_criterion": ["time", 1]
+        }))
+        .unwrap();
+        assert_eq!(
+            Phase::new(None, &config).orientation,
+            PlanetOrientation::SiderealTime
+        );
+
+        // The pole of the planet frame is not the inertial z-axis
+        let (aligned, _) = phase("aligned");
+        let (state, inertial_to_planet) = phase("precession_nutation");
+        let pole = inertial_to_planet.inverse_transform_vector(&Vector3::z());
+        assert!(pole.angle(&Vector3::z()) > 0.1_f64.to_radians());
+
+        // Altitude and gravity are the same in the planet frame
+        assert_almost_eq_rel!(state.altitude, aligned.altitude, 1e-9);
+        assert_almost_eq_rel!(state.altitude, state.altitude_geodetic, 1e-5);
+        assert_almost_eq_rel!(
+            state.altitude_geopotential,
+            aligned.altitude_geopotential,
+            1e-9
+        );
+        assert_almost_eq_rel!(vec
+            inertial_to_planet.transform_vector(&state.gravity_acceleration),
+            aligned.gravity_acceleration,
+            1e-9
+        );
+    }
+
+    #[test]
+    fn stages() {
+        let engine = |thrust_vac: f64| {
+            serde_json::json!({
+                "incidence": [0, 0], "thrust_vac": thrust_vac, "isp_vac": 300, "exit_area": 0
+            })
+        };
+        let configs: Vec<PhaseConfig> = config::from_value(serde_json::json!({
+            "version": config::VERSION,
+            "phases": [
+                {
+                    "init": { "latitude": 0, "longitude": 0, "azimuth": 90, "altitude": 0 },
+                    "vehicle": { "stages": [
+                        { "structure_mass": 1000, "propellant_mass": 2000, "engines": [engine(1e5)] },
+                        { "structure_mass": 500, "propellant_mass": 1000, "engines": [engine(2e4)] },
+                        { "structure_mass": 200, "propellant_mass": 100, "engines": [] },
+                    ]},
+                    "end_criterion": ["time", 70]
+                },
+                { "vehicle": { "separate_stage": true }, "end_criterion": ["time_since_event", 1] }
+            ]
+        }))
+        .unwrap();
+
+        let mut phase = Phase::new(None, &configs[0]);
+        assert_eq!(phase.state.mass, 4800.);
+        phase.run();
+
+        // The first stage was separated when its propellant was depleted
+        let massflow = |thrust_vac: f64| thrust_vac / 300. / STD_GRAVITY;
+        let burn_time = 2000. / massflow(1e5);
+        assert_almost_eq_rel!(
+            phase.state.mass,
+            1800. - (70. - burn_time) * massflow(2e4),
+            1e-6
+        );
+        assert_almost_eq_rel!(
+            phase.state.propellant_mass,
+            1000. - (70. - burn_time) * massflow(2e4),
+            1e-6
+        );
+
+        // The second stage is separated on the event with its remaining
+        // propellant
+        let phase = Phase::new(Some(&phase), &configs[1]);
+        assert_eq!(phase.state.mass, 300.);
+        assert!(!phase.vehicle.can_separate());
+    }
+
+    fn burnout_config(burnout: &str) -> PhaseConfig {
+        serde_json::from_value(serde_json::json!({
+            "init": { "latitude": 0, "longitude": 0, "azimuth": 90, "altitude": 0 },
+            "vehicle": {
+                "structure_mass": 1000,
+                "propellant_mass": 2000,
+                "engines": [{
+                    "incidence": [0, 0], "thrust_vac": 1e5, "isp_vac": 300, "exit_area": 0
+                }],
+                "burnout": burnout
+            },
+            "end_criterion": ["time", 70]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn burnout() {
+        let mut phase = Phase::new(None, &burnout_config("cutoff"));
+        phase.run();
+
+        // The engines were cut off at the depletion of the propellant
+        assert!(phase.state.propellant_mass.abs() < 1e-3);
+        assert_eq!(phase.state.thrust_force_body, Vector3::zeros());
+        assert_eq!(phase.state.massflow, 0.);
+    }
+
+    #[test]
+    #[should_panic(expected = "Propellant depleted")]
+    fn burnout_error() {
+        Phase::new(None, &burnout_config("error")).run();
+    }
+
+    #[test]
+    fn time_since_ignition() {
+        let mut config = burnout_config("cutoff");
+        config.end_criterion = Some((StateVariable::TimeSinceIgnition, 5.));
+        let mut phase = Phase::new(None, &config);
+        phase.run();
+
+        assert_almost_eq_rel!(phase.state.time, 5_f64, 1e-6);
+        assert_almost_eq_rel!(phase.state.time_since_ignition, 5_f64, 1e-6);
+    }
+
+    #[test]
+    fn burn_time() {
+        let mut config = burnout_config("cutoff");
+        config.vehicle.as_mut().unwrap().engines = Some(
+            serde_json::from_value(serde_json::json!([{
+                "incidence": [0, 0], "thrust_vac": 1e5, "isp_vac": 300, "exit_area": 0,
+                "burn_time": 10.25
+            }]))
+            .unwrap(),
+        );
+        config.end_criterion = Some((StateVariable::Time, 20.));
+        let mut phase = Phase::new(None, &config);
+        phase.run();
+
+        // The propellant is consumed up to the burn time within the time step
+        assert_almost_eq_rel!(
+            phase.state.propellant_mass,
+            2000. - 1e5 / 300. / STD_GRAVITY * 10.25,
+            1e-9
+        );
+        assert_eq!(phase.state.massflow, 0.);
+    }
+
+    #[test]
+    fn throttle_limits() {
+        let config = |throttle_limits: serde_json::Value| -> PhaseConfig {
+            serde_json::from_value(serde_json::json!({
+                "init": { "latitude": 0, "longitude": 0, "azimuth": 90, "altitude": 0 },
+                "atmosphere": { "model": "standard_atmosphere_1976" },
+                "vehicle": {
+                    "structure_mass": 3000,
+                    "propellant_mass": 7000,
+                    "engines": [{
+                        "incidence": [0, 0], "thrust_vac": 3e5, "isp_vac": 300, "exit_area": 0
+                    }]
+                },
+                "throttle_limits": throttle_limits,
+                "end_criterion": ["time", 60]
+            }))
+            .unwrap()
+        };
+        let run = |phase: &mut Phase| {
+            let mut states = vec![];
+            while !phase.ended {
+                phase.step();
+                states.push(phase.state.clone());
+            }
+            states
+        };
+
+        let max_dynamic_pressure =
+            |states: &[State]| states.iter().map(|s| s.dynamic_pressure).fold(0., f64::max);
+
+        let states = run(&mut Phase::new(None, &config(serde_json::json!({}))));
+        assert!(max_dynamic_pressure(&states) > 1e5);
+
+        // The dynamic pressure is held at the limit
+        let states = run(&mut Phase::new(
+            None,
+            &config(serde_json::json!({ "max_dynamic_pressure": 2e4 })),
+        ));
+        assert_almost_eq_rel!(max_dynamic_pressure(&states), 2e4_f64, 1e-3);
+
+        let states = run(&mut Phase::new(
+            None,
+            &config(serde_json::json!({ "max_axial_acceleration": 40, "thrust_to_weight": 2 })),
+        ));
+        for state in states {
+            let thrust_to_weight =
+                state.thrust_force_body.norm() / (state.mass * state.gravity_acceleration.norm());
+            assert!(state.vehicle_acceleration_body.x < 40. + 1e-9);
+            assert!(thrust_to_weight < 2. + 1e-9);
+            assert!(
+                (state.vehicle_acceleration_body.x - 40.).abs() < 1e-9
+                    || (thrust_to_weight - 2.).abs() < 1e-9
+            );
+        }
+    }
+    #[test]
+    fn density_gradient() {
+        let config = |altitude: &str| -> PhaseConfig {
+            let table =
+                |data: [f64; 2]| serde_json::json!({ "x": [altitude, [0, 1e4]], "data": data });
+            serde_json::from_value(serde_json::json!({
+                "init": { "latitude": 0, "longitude": 0, "azimuth": 90, "altitude": 0 },
+                "atmosphere": { "model": { "table": {
+                    "temperature": table([290., 230.]),
+                    "pressure": table([1e5, 3e4]),
+                    "density": table([1.2, 0.4])
+                }}},
+                "vehicle": {
+                    "structure_mass": 3000,
+                    "propellant_mass": 7000,
+                    "engines": [{
+                        "incidence": [0, 0], "thrust_vac": 3e5, "isp_vac": 300, "exit_area": 0
+                    }]
+                },
+                "throttle_limits": { "max_dynamic_pressure": 1e9 },
+                "end_criterion": ["time", 20]
+            }))
+            .unwrap()
+        };
+
+        // The limit is the same for tables over any of the altitudes, as the
+        // density gradient is calculated from the position
+        let limits: Vec<f64> = ["altitude", "altitude_geodetic", "altitude_above_ground"]
+            .into_iter()
+            .map(|altitude| {
+                let mut phase = Phase::new(None, &config(altitude));
+                phase.run();
+                phase.throttle_limits.max_dynamic_pressure = Some(phase.state.dynamic_pressure);
+                let inertial_to_body =
+                    inertial_to_body(&phase.inertial_to_launch, phase.state.euler_angles);
+                phase.throttle_limit(&phase.state, &inertial_to_body)
+            })
+            .collect();
+        assert!(limits[0] > 0.);
+        assert_almost_eq_rel!(limits[1], limits[0], 1e-6);
+        assert_almost_eq_rel!(limits[2], limits[0], 1e-6);
+    }
 }
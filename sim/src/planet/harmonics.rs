@@ -0,0 +1,449 @@
+//! Defines the [`GravityField`], a spherical harmonic gravity model of
+//! arbitrary degree and order.
+//!
+//! The normalized coefficients are read from a coefficient file, either in the
+//! ICGEM format (`.gfc`, with a header containing `earth_gravity_constant`
+//! and `radius`), or in the EGM format with lines `n m C S`. Further columns
+//! (e.g. the standard deviations) are ignored.
+//!
+//! The acceleration is calculated with the recursion of the solid harmonics
+//! `V` and `W` by Cunningham, see Montenbruck and Gill: "Satellite Orbits",
+//! p. 66 ff. The recursion is adapted to the normalized coefficients, so that
+//! high degrees do not overflow.
+
+use nalgebra::{vector, Vector3};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+/// Represents an error while loading the coefficient file.
+#[derive(Debug, PartialEq)]
+pub enum GravityFieldError {
+    /// The file could not be read.
+    Io(String),
+    /// The line could not be parsed.
+    InvalidLine(usize),
+    /// The ICGEM file uses unnormalized coefficients.
+    Unnormalized,
+    /// The constant is neither in the file header nor in the configuration.
+    MissingConstant(&'static str),
+    /// The file contains only coefficients up to the given degree.
+    DegreeTooHigh(usize),
+}
+
+impl Display for GravityFieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GravityFieldError::Io(err) => write!(f, "Could not read coefficient file: {err}"),
+            GravityFieldError::InvalidLine(line) => {
+                write!(f, "Invalid line {line} in coefficient file")
+            }
+            GravityFieldError::Unnormalized => {
+                write!(f, "Coefficient file must use normalized coefficients")
+            }
+            GravityFieldError::MissingConstant(name) => {
+                write!(
+                    f,
+                    "\"{name}\" is neither in the coefficient file nor in the configuration"
+                )
+            }
+            GravityFieldError::DegreeTooHigh(degree) => {
+                write!(
+                    f,
+                    "Coefficient file only contains coefficients up to degree {degree}"
+                )
+            }
+        }
+    }
+}
+
+impl Error for GravityFieldError {}
+
+/// Represents a spherical harmonic gravity model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "GravityFieldUnchecked")]
+pub struct GravityField {
+    /// The coefficient file.
+    file: PathBuf,
+    /// The maximum degree and order used.
+    degree: usize,
+    /// The gravitational constant in m^3/s^2.
+    pub gravitational_constant: f64,
+    /// The reference radius of the coefficients in m.
+    reference_radius: f64,
+    /// The normalized coefficients Cnm, indexed with [`index`].
+    #[serde(skip)]
+    c: Vec<f64>,
+    /// The normalized coefficients Snm, indexed with [`index`].
+    #[serde(skip)]
+    s: Vec<f64>,
+}
+
+/// The configuration of the [`GravityField`].
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GravityFieldUnchecked {
+    /// Path to the coefficient file, either in the ICGEM format or with lines
+    /// `n m C S` (EGM format).
+    file: PathBuf,
+    /// The maximum degree and order used.
+    degree: usize,
+    /// The gravitational constant in m^3/s^2. Defaults to the value in the
+    /// file header.
+    gravitational_constant: Option<f64>,
+    /// The reference radius of the coefficients in m. Defaults to the value
+    /// in the file header.
+    reference_radius: Option<f64>,
+}
+
+impl TryFrom<GravityFieldUnchecked> for GravityField {
+    type Error = GravityFieldError;
+
+    fn try_from(value: GravityFieldUnchecked) -> Result<Self, Self::Error> {
+        let content = std::fs::read_to_string(&value.file)
+            .map_err(|err| GravityFieldError::Io(format!("{}: {err}", value.file.display())))?;
+
+        let mut field = Self::parse(&content, value.degree)?;
+        field.file = value.file;
+        if let Some(gravitational_constant) = value.gravitational_constant {
+            field.gravitational_constant = gravitational_constant;
+        }
+        if let Some(reference_radius) = value.reference_radius {
+            field.reference_radius = reference_radius;
+        }
+
+        if field.gravitational_constant.is_nan() {
+            return Err(GravityFieldError::MissingConstant("gravitational_constant"));
+        }
+        if field.reference_radius.is_nan() {
+            return Err(GravityFieldError::MissingConstant("reference_radius"));
+        }
+
+        Ok(field)
+    }
+}
+
+impl JsonSchema for GravityField {
+    fn schema_name() -> String {
+        "GravityField".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        GravityFieldUnchecked::json_schema(gen)
+    }
+}
+
+/// Returns the index of the coefficient of degree `n` and order `m`.
+fn index(n: usize, m: usize) -> usize {
+    n * (n + 1) / 2 + m
+}
+
+/// Parses a number, also with Fortran `D` exponents.
+fn parse_number(word: &str) -> Option<f64> {
+    word.replace(['d', 'D'], "e").parse().ok()
+}
+
+impl GravityField {
+    /// Parses the content of a coefficient file up to `degree`. Constants
+    /// which are not in the file header are set to NaN.
+    fn parse(content: &str, degree: usize) -> Result<Self, GravityFieldError> {
+        let mut field = Self {
+            file: PathBuf::new(),
+            degree,
+            gravitational_constant: f64::NAN,
+            reference_radius: f64::NAN,
+            c: vec![0.; index(degree + 1, 0)],
+            s: vec![0.; index(degree + 1, 0)],
+        };
+        field.c[0] = 1.;
+
+        let mut lines = content.lines().enumerate();
+
+        if content.contains("end_of_head") {
+            for (i, line) in lines.by_ref() {
+                let mut words = line.split_whitespace();
+                let (key, value) = (words.next(), words.next());
+                match key {
+                    Some("end_of_head") => break,
+                    Some("earth_gravity_constant" | "gravity_constant") => {
+                        field.gravitational_constant = value
+                            .and_then(parse_number)
+                            .ok_or(GravityFieldError::InvalidLine(i + 1))?;
+                    }
+                    Some("radius") => {
+                        field.reference_radius = value
+                            .and_then(parse_number)
+                            .ok_or(GravityFieldError::InvalidLine(i + 1))?;
+                    }
+                    Some("norm") if value == Some("unnormalized") => {
+                        return Err(GravityFieldError::Unnormalized)
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        let mut max_degree = 0;
+        for (i, line) in lines {
+            let mut words = line
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|word| !word.is_empty())
+                .peekable();
+
+            match words.peek() {
+                None => continue,
+                Some(word) if word.starts_with('#') => continue,
+                Some(&"gfc" | &"gfct") => {
+                    words.next();
+                }
+                // Time variable terms (e.g. "trnd", "acos", "asin") are ignored
+                Some(word) if word.starts_with(char::is_alphabetic) => continue,
+                _ => (),
+            }
+
+            let mut numbers = words.take(4).map(parse_number);
+            let (Some(Some(n)), Some(Some(m)), Some(Some(c)), Some(Some(s))) = (
+                numbers.next(),
+                numbers.next(),
+                numbers.next(),
+                numbers.next(),
+            ) else {
+                return Err(GravityFieldError::InvalidLine(i + 1));
+            };
+            if n.fract() != 0. || m.fract() != 0. || n < 0. || m < 0. || m > n {
+                return Err(GravityFieldError::InvalidLine(i + 1));
+            }
+
+            let (n, m) = (n as usize, m as usize);
+            max_degree = max_degree.max(n);
+            if n <= degree {
+                field.c[index(n, m)] = c;
+                field.s[index(n, m)] = s;
+            }
+        }
+
+        if max_degree < degree {
+            return Err(GravityFieldError::DegreeTooHigh(max_degree));
+        }
+
+        Ok(field)
+    }
+
+    /// Calculates the normalized solid harmonics `V` and `W` up to degree
+    /// `degree + 1`, as needed for the acceleration.
+    fn solid_harmonics(&self, position: Vector3<f64>) -> (Vec<f64>, Vec<f64>) {
+        let size = self.degree + 2;
+        let mut v = vec![0.; index(size, 0)];
+        let mut w = vec![0.; index(size, 0)];
+
+        let r_sq = position.norm_squared();
+        let rho = self.reference_radius / r_sq;
+        let (x0, y0, z0) = (position.x * rho, position.y * rho, position.z * rho);
+        let rho_sq = self.reference_radius * rho;
+
+        v[0] = self.reference_radius / r_sq.sqrt();
+
+        for m in 0..size {
+            let mf = m as f64;
+            if m > 0 {
+                // Diagonal terms
+                let f = if m == 1 {
+                    3_f64.sqrt()
+                } else {
+                    ((2. * mf + 1.) / (2. * mf)).sqrt()
+                };
+                let (v_prev, w_prev) = (v[index(m - 1, m - 1)], w[index(m - 1, m - 1)]);
+                v[index(m, m)] = f * (x0 * v_prev - y0 * w_prev);
+                w[index(m, m)] = f * (x0 * w_prev + y0 * v_prev);
+            }
+
+            for n in m + 1..size {
+                let nf = n as f64;
+                let a = ((2. * nf - 1.) * (2. * nf + 1.) / ((nf - mf) * (nf + mf))).sqrt();
+                v[index(n, m)] = a * z0 * v[index(n - 1, m)];
+                w[index(n, m)] = a * z0 * w[index(n - 1, m)];
+
+                if n >= m + 2 {
+                    let b = ((2. * nf + 1.) * (nf + mf - 1.) * (nf - mf - 1.)
+                        / ((2. * nf - 3.) * (nf + mf) * (nf - mf)))
+                        .sqrt();
+                    v[index(n, m)] -= b * rho_sq * v[index(n - 2, m)];
+                    w[index(n, m)] -= b * rho_sq * w[index(n - 2, m)];
+                }
+            }
+        }
+
+        (v, w)
+    }
+
+    /// Calculates the gravitational acceleration in m/s^2. The position must
+    /// be given in the planet-fixed frame, and the acceleration is returned in
+    /// the same frame.
+    pub fn acceleration(&self, position: Vector3<f64>) -> Vector3<f64> {
+        let (v, w) = self.solid_harmonics(position);
+
+        let (mut ax, mut ay, mut az) = (0., 0., 0.);
+        for n in 0..=self.degree {
+            let nf = n as f64;
+            let q = (2. * nf + 1.) / (2. * nf + 3.);
+
+            for m in 0..=n {
+                let mf = m as f64;
+                let (c, s) = (self.c[index(n, m)], self.s[index(n, m)]);
+
+                if m == 0 {
+                    let f1 = (q * (nf + 1.) * (nf + 2.) / 2.).sqrt();
+                    ax -= f1 * c * v[index(n + 1, 1)];
+                    ay -= f1 * c * w[index(n + 1, 1)];
+                } else {
+                    let f1 = (q * (nf + mf + 1.) * (nf + mf + 2.)).sqrt();
+                    let k = if m == 1 { 2. } else { 1. };
+                    let f2 = (k * q * (nf - mf + 1.) * (nf - mf + 2.)).sqrt();
+                    let (v1, w1) = (v[index(n + 1, m + 1)], w[index(n + 1, m + 1)]);
+                    let (v2, w2) = (v[index(n + 1, m - 1)], w[index(n + 1, m - 1)]);
+                    ax += 0.5 * (f1 * (-c * v1 - s * w1) + f2 * (c * v2 + s * w2));
+                    ay += 0.5 * (f1 * (-c * w1 + s * v1) + f2 * (-c * w2 + s * v2));
+                }
+
+                let f3 = (q * (nf + mf + 1.) * (nf - mf + 1.)).sqrt();
+                az += f3 * (-c * v[index(n + 1, m)] - s * w[index(n + 1, m)]);
+            }
+        }
+
+        self.gravitational_constant / self.reference_radius.powi(2) * vector![ax, ay, az]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_almost_eq_rel;
+    use crate::planet::EARTH_SMITHSONIAN;
+
+    const ICGEM: &str = "
+product_type               gravity_field
+modelname                  TEST
+earth_gravity_constant     0.3986004415D+15
+radius                     0.63781363E+07
+max_degree                 3
+norm                       fully_normalized
+end_of_head ====================================
+gfc   0    0    1.0               0.0
+gfc   2    0   -0.484165143790815e-03  0.0   1e-12 1e-12
+gfc   2    1   -0.206615509074176e-09  0.138441389137979e-08
+trnd  2    1    1e-12  1e-12
+gfc   2    2    0.243938357328313e-05 -0.140027370385934e-05
+gfc   3    0    0.957161207093473e-06  0.0
+";
+
+    /// Builds a field of `degree` with only the central term.
+    fn empty(degree: usize) -> GravityField {
+        let mut field = GravityField::parse("", 0).unwrap();
+        field.degree = degree;
+        field.c = vec![0.; index(degree + 1, 0)];
+        field.s = vec![0.; index(degree + 1, 0)];
+        field.c[0] = 1.;
+        field
+    }
+
+    /// Calculates the potential with the solid harmonics. Used to check the
+    /// acceleration with numerical derivatives.
+    fn potential(field: &GravityField, position: Vector3<f64>) -> f64 {
+        let (v, w) = field.solid_harmonics(position);
+        let mut sum = 0.;
+        for n in 0..=field.degree {
+            for m in 0..=n {
+                sum +=
+                    field.c[index(n, m)] * v[index(n, m)] + field.s[index(n, m)] * w[index(n, m)];
+            }
+        }
+        field.gravitational_constant / field.reference_radius * sum
+    }
+
+    #[test]
+    fn parse_icgem() {
+        let field = GravityField::parse(ICGEM, 2).unwrap();
+
+        assert_eq!(field.gravitational_constant, 3.986004415e14);
+        assert_eq!(field.reference_radius, 6.3781363e6);
+        assert_eq!(field.c[index(2, 2)], 0.243938357328313e-05);
+        assert_eq!(field.s[index(2, 1)], 0.138441389137979e-08);
+        assert_eq!(field.c.len(), index(3, 0));
+
+        assert_eq!(
+            GravityField::parse(ICGEM, 4).unwrap_err(),
+            GravityFieldError::DegreeTooHigh(3)
+        );
+    }
+
+    #[test]
+    fn parse_egm() {
+        let content = "  2  0 -0.48416537173600D-03  0.00000000000000D+00\n  2, 1, 1e-9, 2e-9\n";
+
+        let field = GravityField::parse(content, 2).unwrap();
+
+        assert_eq!(field.c[0], 1.);
+        assert_eq!(field.c[index(2, 0)], -0.484165371736e-3);
+        assert_eq!(field.s[index(2, 1)], 2e-9);
+        assert!(field.gravitational_constant.is_nan());
+
+        GravityField::parse("2 0 x 0.0", 2).unwrap_err();
+    }
+
+    #[test]
+    fn compare_zonal() {
+        // Build the zonal J2 to J4 terms of the Smithsonian model
+        let mut field = empty(4);
+        field.gravitational_constant = EARTH_SMITHSONIAN.gravitational_parameters[0];
+        field.reference_radius = EARTH_SMITHSONIAN.equatorial_radius;
+        for n in 2..=4 {
+            field.c[index(n, 0)] =
+                -EARTH_SMITHSONIAN.gravitational_parameters[n - 1] / f64::sqrt(2. * n as f64 + 1.);
+        }
+
+        for position in [
+            vector![6.4e6, 1e5, 2e5],
+            vector![-3e6, 4e6, 5e6],
+            vector![1e6, -2e6, -7e6],
+            vector![7e6, 0., 0.],
+        ] {
+            assert_almost_eq_rel!(
+                vec field.acceleration(position),
+                EARTH_SMITHSONIAN.zonal_gravity(position),
+                1e-10
+            );
+        }
+    }
+
+    #[test]
+    fn compare_potential() {
+        // Arbitrary coefficients up to degree and order 6
+        let mut field = empty(6);
+        field.gravitational_constant = 3.986004415e14;
+        field.reference_radius = 6.3781363e6;
+        for n in 1..=6 {
+            for m in 0..=n {
+                field.c[index(n, m)] = 1e-3 * ((n * 7 + m * 3) % 5) as f64 / n as f64;
+                if m > 0 {
+                    field.s[index(n, m)] = -1e-3 * ((n * 3 + m * 5) % 7) as f64 / n as f64;
+                }
+            }
+        }
+
+        const H: f64 = 1.;
+        for position in [vector![6.4e6, 1e5, 2e5], vector![-3e6, 4e6, 5e6]] {
+            let gradient = Vector3::from_fn(|i, _| {
+                let mut delta = Vector3::zeros();
+                delta[i] = H;
+                (potential(&field, position + delta) - potential(&field, position - delta))
+                    / (2. * H)
+            });
+
+            assert_almost_eq_rel!(vec field.acceleration(position), gradient, 1e-6);
+        }
+    }
+}
@@ -0,0 +1,421 @@
+// Created by Tibor Völcker (tiborvoelcker@hotmail.de) on 17.11.23
+// Last modified by Tibor Völcker on 22.05.24
+// Copyright (c) 2023 Tibor Völcker (tiborvoelcker@hotmail.de)
+
+//! Defines the [`Planet`] struct, which handles all functions
+//! regarding the planet.
+//!
+//! The gravity is either calculated with the zonal harmonics J2 to J4, or with
+//! a spherical harmonic [`GravityField`] of arbitrary degree and order.
+
+mod harmonics;
+mod terrain;
+
+use crate::atmosphere::{AtmosphereModel, ExponentialAtmosphere, CARBON_DIOXIDE};
+use crate::config::PlanetConfig;
+use crate::utils::constants::{
+    CUBIC_METER_PER_CUBIC_FOOT, METER_PER_FOOT, MOON_GRAVITATIONAL_PARAMETER,
+};
+use nalgebra::{vector, Rotation3, Vector3};
+use serde::Serialize;
+
+pub use harmonics::GravityField;
+pub use terrain::Terrain;
+
+/// Represents the planet.
+///
+/// Its method use the parameters together with some state variables to
+/// calculate derived state variables.
+#[derive(Debug, Clone, Serialize)]
+pub struct Planet {
+    /// The equatorial radius im m.
+    pub equatorial_radius: f64,
+    /// The polar radius in m.
+    pub polar_radius: f64,
+    /// The gravitational harmonics J1 to J4. The first one is the
+    /// gravitational constant in mˆ3/sˆ2.
+    gravitational_parameters: [f64; 4],
+    /// The rotational rate in rad/s.
+    pub rotation_rate: f64,
+    /// The spherical harmonic gravity model. If it is set, it is used instead
+    /// of the gravitational harmonics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gravity_field: Option<GravityField>,
+    /// The atmosphere model used if the atmosphere is enabled.
+    #[serde(skip)]
+    pub atmosphere_model: AtmosphereModel,
+}
+
+impl Default for Planet {
+    fn default() -> Self {
+        EARTH_SPHERICAL
+    }
+}
+
+impl Planet {
+    /// Updates itself with the new configuration parameters.
+    pub fn update_with_config(config: &PlanetConfig) -> Self {
+        match config {
+            PlanetConfig::Spherical => EARTH_SPHERICAL,
+            PlanetConfig::Fisher1960 => EARTH_FISHER_1960,
+            PlanetConfig::Smithsonian => EARTH_SMITHSONIAN,
+            PlanetConfig::Moon => MOON,
+            PlanetConfig::Mars => MARS,
+            PlanetConfig::Venus => VENUS,
+            PlanetConfig::Custom {
+                equatorial_radius,
+                polar_radius,
+                gravitational_parameters,
+                rotation_rate,
+            } => Planet {
+                equatorial_radius: *equatorial_radius,
+                polar_radius: *polar_radius,
+                gravitational_parameters: *gravitational_parameters,
+                rotation_rate: *rotation_rate,
+                gravity_field: None,
+                atmosphere_model: AtmosphereModel::StandardAtmosphere1962,
+            },
+            PlanetConfig::SphericalHarmonics {
+                gravity_field,
+                equatorial_radius,
+                polar_radius,
+                rotation_rate,
+            } => Planet {
+                equatorial_radius: equatorial_radius.unwrap_or(EARTH_SMITHSONIAN.equatorial_radius),
+                polar_radius: polar_radius.unwrap_or(EARTH_SMITHSONIAN.polar_radius),
+                gravitational_parameters: [gravity_field.gravitational_constant, 0., 0., 0.],
+                rotation_rate: rotation_rate.unwrap_or(EARTH_SMITHSONIAN.rotation_rate),
+                gravity_field: Some(gravity_field.clone()),
+                atmosphere_model: AtmosphereModel::StandardAtmosphere1962,
+            },
+        }
+    }
+}
+
+/// Defines the default implementation of a spherical earth.
+///
+/// This means the equatorial radius is the same as the polar radius, and only
+/// the the gravitational constant is used.
+pub const EARTH_SPHERICAL: Planet = Planet {
+    equatorial_radius: 2.0925741e7 * METER_PER_FOOT,
+    polar_radius: 2.0925741e7 * METER_PER_FOOT,
+    gravitational_parameters: [1.4076539e16 * CUBIC_METER_PER_CUBIC_FOOT, 0., 0., 0.],
+    rotation_rate: 7.29211e-5,
+    gravity_field: None,
+    atmosphere_model: AtmosphereModel::StandardAtmosphere1962,
+};
+
+/// Defines the default implementation of the 1960 Fisher earth model, as
+/// defined in [3, p. IV-1].
+///
+/// It uses gravitational harmonics up to J2.
+const EARTH_FISHER_1960: Planet = Planet {
+    equatorial_radius: 2.0925741e7 * METER_PER_FOOT,
+    polar_radius: 2.0855590e7 * METER_PER_FOOT,
+    gravitational_parameters: [1.4076539e16 * CUBIC_METER_PER_CUBIC_FOOT, 1.0823e-3, 0., 0.],
+    rotation_rate: 7.29211e-5,
+    gravity_field: None,
+    atmosphere_model: AtmosphereModel::StandardAtmosphere1962,
+};
+
+/// Defines the default implementation of the Smithsonial earth model, as
+/// defined in [3, p. IV-1].
+///
+/// It uses gravitational harmonics up to J4.
+const EARTH_SMITHSONIAN: Planet = Planet {
+    equatorial_radius: 2.0925741e7 * METER_PER_FOOT,
+    polar_radius: 2.0855590e7 * METER_PER_FOOT,
+    gravitational_parameters: [
+        1.407645794e16 * CUBIC_METER_PER_CUBIC_FOOT,
+        1.082639e-3,
+        -2.565e-6,
+        -1.608e-6,
+    ],
+    rotation_rate: 7.29211e-5,
+    gravity_field: None,
+    atmosphere_model: AtmosphereModel::StandardAtmosphere1962,
+};
+
+/// Defines the Moon, with the values of the NASA planetary fact sheet.
+///
+/// It uses gravitational harmonics up to J2 and has no atmosphere.
+const MOON: Planet = Planet {
+    equatorial_radius: 1.7381e6,
+    polar_radius: 1.736e6,
+    gravitational_parameters: [MOON_GRAVITATIONAL_PARAMETER, 2.027e-4, 0., 0.],
+    rotation_rate: 2.6617e-6,
+    gravity_field: None,
+    atmosphere_model: AtmosphereModel::NoAtmosphere,
+};
+
+/// Defines Mars, with the values of the NASA planetary fact sheet.
+///
+/// It uses gravitational harmonics up to J2 and the Mars atmosphere model (see
+/// [`crate::atmosphere::AtmosphereModel::Mars`]).
+const MARS: Planet = Planet {
+    equatorial_radius: 3.3962e6,
+    polar_radius: 3.3762e6,
+    gravitational_parameters: [4.282837e13, 1.96045e-3, 0., 0.],
+    rotation_rate: 7.088218e-5,
+    gravity_field: None,
+    atmosphere_model: AtmosphereModel::Mars,
+};
+
+/// Defines Venus, with the values of the NASA planetary fact sheet.
+///
+/// It uses gravitational harmonics up to J2 and an exponential CO2 atmosphere
+/// with a surface pressure of 92 bar, a temperature of 737 K and a scale
+/// height of 15.9 km. Venus rotates retrograde, so the rotation rate is
+/// negative.
+const VENUS: Planet = Planet {
+    equatorial_radius: 6.0518e6,
+    polar_radius: 6.0518e6,
+    gravitational_parameters: [3.2485859e14, 4.458e-6, 0., 0.],
+    rotation_rate: -2.9924e-7,
+    gravity_field: None,
+    atmosphere_model: AtmosphereModel::Exponential(ExponentialAtmosphere {
+        surface_pressure: 9.2e6,
+        temperature: 737.,
+        scale_height: 1.59e4,
+        gas: CARBON_DIOXIDE,
+    }),
+};
+
+impl Planet {
+    /// Calculate the altitude in m above the oblate surface from the position
+    /// in the planet frame.
+    pub fn altitude(&self, position_planet: Vector3<f64>) -> f64 {
+        let k = (self.equatorial_radius / self.polar_radius).powi(2);
+
+        let geocentric_lat = f64::asin(position_planet.z / position_planet.norm());
+
+        let distance_to_surface =
+            self.equatorial_radius / f64::sqrt(1. + (k - 1.) * geocentric_lat.sin().powi(2));
+
+        position_planet.norm() - distance_to_surface
+    }
+
+    /// Calculate the geodetic latitude and longitude in rad and the geodetic
+    /// altitude in m (i.e. along the surface normal) from the position in the
+    /// planet frame.
+    ///
+    /// Uses the iterative method of Bowring, which converges to machine
+    /// precision in a few iterations.
+    pub fn geodetic(&self, position_planet: Vector3<f64>) -> (f64, f64, f64) {
+        let (a, b) = (self.equatorial_radius, self.polar_radius);
+        let e2 = 1. - (b / a).powi(2);
+        let ep2 = (a / b).powi(2) - 1.;
+
+        let rho = f64::hypot(position_planet.x, position_planet.y);
+        let z = position_planet.z;
+        let long = f64::atan2(position_planet.y, position_planet.x);
+
+        // Reduced (parametric) latitude
+        let mut beta = f64::atan2(a * z, b * rho);
+        let mut lat = 0.;
+        for _ in 0..3 {
+            lat = f64::atan2(
+                z + ep2 * b * beta.sin().powi(3),
+                rho - e2 * a * beta.cos().powi(3),
+            );
+            beta = f64::atan2(b * lat.sin(), a * lat.cos());
+        }
+
+        // Radius of curvature in the prime vertical
+        let n = a / f64::sqrt(1. - e2 * lat.sin().powi(2));
+        let alt = rho * lat.cos() + (z + e2 * n * lat.sin()) * lat.sin() - n;
+
+        (lat, long, alt)
+    }
+
+    /// Calculate the position in m in the planet frame from the geodetic
+    /// latitude and longitude in rad and the geodetic altitude in m.
+    pub fn position_from_geodetic(&self, lat: f64, long: f64, alt: f64) -> Vector3<f64> {
+        let e2 = 1. - (self.polar_radius / self.equatorial_radius).powi(2);
+
+        // Radius of curvature in the prime vertical
+        let n = self.equatorial_radius / f64::sqrt(1. - e2 * lat.sin().powi(2));
+
+        vector![
+            (n + alt) * lat.cos() * long.cos(),
+            (n + alt) * lat.cos() * long.sin(),
+            (n * (1. - e2) + alt) * lat.sin()
+        ]
+    }
+
+    /// Calculate the geopotential altitude in m from the position in the
+    /// planet frame (used for the atmospheric model).
+    pub fn geopotential_altitude(&self, position_planet: Vector3<f64>) -> f64 {
+        let altitude = self.altitude(position_planet);
+        let avg_altitude = 0.5 * (self.equatorial_radius + self.polar_radius);
+        avg_altitude * altitude / (avg_altitude + altitude)
+    }
+
+    /// Calculate the velocity with respect to the planet in m/s in the
+    /// inertial frame. The transformation to the planet frame defines the
+    /// rotation axis.
+    pub fn velocity_planet(
+        &self,
+        position: Vector3<f64>,
+        velocity: Vector3<f64>,
+        inertial_to_planet: &Rotation3<f64>,
+    ) -> Vector3<f64> {
+        let rotation_axis = inertial_to_planet.inverse_transform_vector(&Vector3::z());
+        velocity - (self.rotation_rate * rotation_axis).cross(&position)
+    }
+
+    /// Get the gravitational constant in m^3/s^2.
+    fn mu(&self) -> f64 {
+        self.gravitational_parameters[0]
+    }
+
+    /// Calculate the gravitational acceleration in m/s^2 in the inertial
+    /// frame. It is calculated in the planet frame, as the gravity models are
+    /// defined about the planet's pole.
+    pub fn gravity(
+        &self,
+        position: Vector3<f64>,
+        inertial_to_planet: &Rotation3<f64>,
+    ) -> Vector3<f64> {
+        let position_planet = inertial_to_planet.transform_vector(&position);
+        let acceleration = match &self.gravity_field {
+            Some(field) => field.acceleration(position_planet),
+            None => self.zonal_gravity(position_planet),
+        };
+
+        inertial_to_planet.inverse_transform_vector(&acceleration)
+    }
+
+    /// Calculate the gravitational acceleration in m/s^2 in the planet frame
+    /// with the zonal harmonics, according to [3, p. IV-3 f.]
+    #[allow(non_snake_case)]
+    fn zonal_gravity(&self, position: Vector3<f64>) -> Vector3<f64> {
+        let r = position.norm();
+        let R = self.equatorial_radius / r;
+        let Z = position.z / r;
+        let J = 3. / 2. * self.gravitational_parameters[1];
+        let H = 5. / 2. * self.gravitational_parameters[2];
+        let D = -35. / 8. * self.gravitational_parameters[3];
+        let P = 1.
+            + J * R.powi(2) * (1. - 5. * Z.powi(2))
+            + H * R.powi(3) / r * (3. - 7. * Z.powi(2)) * position.z
+            + D * R.powi(4) * (9. * Z.powi(4) - 6. * Z.powi(2) + 3. / 7.);
+
+        vector![
+            -self.mu() * position.x / r.powi(3) * P,
+            -self.mu() * position.y / r.powi(3) * P,
+            -self.mu() / r.powi(3)
+                * ((1. + J * R.powi(2) * (3. - 5. * Z.powi(2))) * position.z
+                    + H * R.powi(3) / r
+                        * (6. * position.z.powi(2)
+                            - 7. * position.z.powi(2) * Z.powi(2)
+                            - 3. / 5. * r.powi(2))
+                    + D * R.powi(4) * (15. / 7. - 10. * Z.powi(2) + 9. * Z.powi(4)) * position.z)
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_almost_eq_rel;
+
+    use super::*;
+    use crate::example_data::DATA_POINTS;
+
+    #[test]
+    fn test_environment() {
+        const EPSILON: f64 = 0.001;
+
+        // The first two altitudes seem to be not as accurate!
+        for data_point in DATA_POINTS[..2].iter() {
+            const EPSILON: f64 = 0.005;
+
+            print!("Testing {} m altitude ... ", data_point.altitude);
+
+            assert_almost_eq_rel!(
+                EARTH_SPHERICAL.altitude(data_point.position),
+                data_point.altitude,
+                EPSILON
+            );
+            assert_almost_eq_rel!(
+                vec EARTH_SPHERICAL.gravity(data_point.position, &Rotation3::identity()),
+                data_point.gravity_acceleration(),
+                EPSILON
+            );
+
+            println!("ok");
+        }
+
+        for data_point in DATA_POINTS[2..].iter() {
+            print!("Testing {} m altitude ... ", data_point.altitude);
+
+            assert_almost_eq_rel!(
+                EARTH_SPHERICAL.altitude(data_point.position),
+                data_point.altitude,
+                EPSILON
+            );
+            assert_almost_eq_rel!(
+                vec EARTH_SPHERICAL.gravity(data_point.position, &Rotation3::identity()),
+                data_point.gravity_acceleration(),
+                EPSILON
+            );
+
+            println!("ok");
+        }
+    }
+
+    #[test]
+    fn surface_gravity() {
+        // Surface gravity from the NASA planetary fact sheets
+        for (planet, gravity) in [(MOON, 1.62_f64), (MARS, 3.71), (VENUS, 8.87)] {
+            let position = vector![planet.equatorial_radius, 0., 0.];
+
+            assert_almost_eq_rel!(
+                planet.gravity(position, &Rotation3::identity()).norm(),
+                gravity,
+                0.01
+            );
+        }
+    }
+
+    #[test]
+    fn geodetic() {
+        // Cape Canaveral: 28.5° N, 279.4° E, 1 km altitude
+        let position = EARTH_FISHER_1960.position_from_geodetic(
+            28.5_f64.to_radians(),
+            279.4_f64.to_radians(),
+            1e3,
+        );
+        let (lat, long, alt) = EARTH_FISHER_1960.geodetic(position);
+        assert_almost_eq_rel!(lat.to_degrees(), 28.5_f64, 1e-12);
+        assert_almost_eq_rel!(long.to_degrees() + 360., 279.4_f64, 1e-12);
+        assert_almost_eq_rel!(alt, 1e3_f64, 1e-8);
+
+        // The geocentric latitude is smaller on the surface
+        let geocentric_lat = f64::atan(
+            (EARTH_FISHER_1960.polar_radius / EARTH_FISHER_1960.equatorial_radius).powi(2)
+                * 28.5_f64.to_radians().tan(),
+        );
+        let position = EARTH_FISHER_1960.position_from_geodetic(28.5_f64.to_radians(), 0., 0.);
+        assert_almost_eq_rel!(f64::atan2(position.z, position.x), geocentric_lat, 1e-12);
+
+        // Poles and equator
+        let (lat, _, alt) = EARTH_FISHER_1960.geodetic(vector![0., 0., -7e6]);
+        assert_almost_eq_rel!(lat.to_degrees(), -90_f64, 1e-12);
+        assert_almost_eq_rel!(alt, 7e6 - EARTH_FISHER_1960.polar_radius, 1e-9);
+        let (lat, _, alt) = EARTH_FISHER_1960.geodetic(vector![0., 7e6, 0.]);
+        assert_almost_eq_rel!(lat, 0_f64, 1e-12);
+        assert_almost_eq_rel!(alt, 7e6 - EARTH_FISHER_1960.equatorial_radius, 1e-9);
+
+        // High altitudes
+        let position = EARTH_FISHER_1960.position_from_geodetic(
+            -60_f64.to_radians(),
+            10_f64.to_radians(),
+            3.6e7,
+        );
+        let (lat, long, alt) = EARTH_FISHER_1960.geodetic(position);
+        assert_almost_eq_rel!(lat.to_degrees(), -60_f64, 1e-12);
+        assert_almost_eq_rel!(long.to_degrees(), 10_f64, 1e-12);
+        assert_almost_eq_rel!(alt, 3.6e7_f64, 1e-12);
+    }
+}
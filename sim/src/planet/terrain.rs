@@ -0,0 +1,298 @@
+//! Defines the [`Terrain`], a grid of the terrain elevation over the geodetic
+//! latitude and longitude.
+//!
+//! The grid is read from a file in the ESRI ASCII grid format, with a header
+//! of `ncols`, `nrows`, `xllcorner` (or `xllcenter`), `yllcorner` (or
+//! `yllcenter`), `cellsize` and optionally `nodata_value`, followed by the
+//! elevations in m. The first row is the northernmost one. The coordinates
+//! are the longitude and geodetic latitude in °.
+//!
+//! The elevation is interpolated linearly between the cell centers. Outside
+//! of the grid and in cells without data, the elevation is zero, i.e. the
+//! reference ellipsoid. Grids spanning all longitudes are interpolated across
+//! the antimeridian.
+
+use crate::state::StateVariable;
+use crate::utils::{Interpolator, Table2D};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::f64::consts::TAU;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+/// Represents an error while loading the terrain grid.
+#[derive(Debug, PartialEq)]
+pub enum TerrainError {
+    /// The file could not be read.
+    Io(String),
+    /// The header entry is missing or invalid.
+    InvalidHeader(&'static str),
+    /// The line could not be parsed or has the wrong number of values.
+    InvalidLine(usize),
+    /// The number of rows does not match the header.
+    InvalidRows(usize),
+}
+
+impl Display for TerrainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TerrainError::Io(err) => write!(f, "Could not read terrain file: {err}"),
+            TerrainError::InvalidHeader(name) => {
+                write!(f, "Missing or invalid \"{name}\" in terrain file header")
+            }
+            TerrainError::InvalidLine(line) => write!(f, "Invalid line {line} in terrain file"),
+            TerrainError::InvalidRows(rows) => {
+                write!(f, "Terrain file contains {rows} rows instead of \"nrows\"")
+            }
+        }
+    }
+}
+
+impl Error for TerrainError {}
+
+/// Represents the terrain elevation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "TerrainUnchecked")]
+pub struct Terrain {
+    /// The grid file.
+    file: PathBuf,
+    /// The elevation in m over the geodetic latitude and the longitude in rad.
+    #[serde(skip)]
+    table: Table2D,
+}
+
+/// The configuration of the [`Terrain`].
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TerrainUnchecked {
+    /// Path to the terrain grid in the ESRI ASCII grid format.
+    file: PathBuf,
+}
+
+impl TryFrom<TerrainUnchecked> for Terrain {
+    type Error = TerrainError;
+
+    fn try_from(value: TerrainUnchecked) -> Result<Self, Self::Error> {
+        let content = std::fs::read_to_string(&value.file)
+            .map_err(|err| TerrainError::Io(format!("{}: {err}", value.file.display())))?;
+
+        Ok(Self {
+            table: Self::parse(&content)?,
+            file: value.file,
+        })
+    }
+}
+
+impl JsonSchema for Terrain {
+    fn schema_name() -> String {
+        "Terrain".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        TerrainUnchecked::json_schema(gen)
+    }
+}
+
+impl Terrain {
+    /// Parses the content of an ESRI ASCII grid.
+    fn parse(content: &str) -> Result<Table2D, TerrainError> {
+        let mut lines = content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .peekable();
+
+        // Header lines start with a keyword
+        let mut header = Vec::new();
+        while let Some((_, line)) =
+            lines.next_if(|(_, line)| line.trim_start().starts_with(char::is_alphabetic))
+        {
+            let mut words = line.split_whitespace();
+            if let (Some(key), Some(value)) = (words.next(), words.next()) {
+                header.push((key.to_ascii_lowercase(), value));
+            }
+        }
+        let get = |keys: &[&'static str]| {
+            header
+                .iter()
+                .find(|(key, _)| keys.contains(&key.as_str()))
+                .map(|(key, value)| (key.as_str(), *value))
+        };
+        let number = |key: &'static str| -> Result<f64, TerrainError> {
+            get(&[key])
+                .and_then(|(_, value)| value.parse().ok())
+                .ok_or(TerrainError::InvalidHeader(key))
+        };
+
+        let columns = number("ncols")?;
+        let rows = number("nrows")?;
+        let cellsize = number("cellsize")?;
+        if columns.fract() != 0. || columns < 2. {
+            return Err(TerrainError::InvalidHeader("ncols"));
+        }
+        if rows.fract() != 0. || rows < 2. {
+            return Err(TerrainError::InvalidHeader("nrows"));
+        }
+        if cellsize <= 0. {
+            return Err(TerrainError::InvalidHeader("cellsize"));
+        }
+        let (columns, rows) = (columns as usize, rows as usize);
+        let nodata = number("nodata_value").ok();
+
+        // Coordinates of the lower left cell center
+        let center = |corner: &'static str, center: &'static str| {
+            let (key, value) = get(&[corner, center]).ok_or(TerrainError::InvalidHeader(corner))?;
+            let value: f64 = value
+                .parse()
+                .map_err(|_| TerrainError::InvalidHeader(corner))?;
+            if key == corner {
+                Ok(value + cellsize / 2.)
+            } else {
+                Ok(value)
+            }
+        };
+        let long = center("xllcorner", "xllcenter")?;
+        let lat = center("yllcorner", "yllcenter")?;
+
+        let mut data = Vec::with_capacity(rows);
+        for (i, line) in lines {
+            let row = line
+                .split_whitespace()
+                .map(|value| {
+                    let value: f64 = value.parse().ok()?;
+                    Some(if Some(value) == nodata { 0. } else { value })
+                })
+                .collect::<Option<Box<[f64]>>>()
+                .filter(|row| row.len() == columns)
+                .ok_or(TerrainError::InvalidLine(i + 1))?;
+            data.push(row);
+        }
+        if data.len() != rows {
+            return Err(TerrainError::InvalidRows(data.len()));
+        }
+        // The first row is the northernmost one
+        data.reverse();
+
+        let latitudes: Vec<f64> = (0..rows)
+            .map(|i| (lat + i as f64 * cellsize).to_radians())
+            .collect();
+        let mut longitudes: Vec<f64> = (0..columns)
+            .map(|i| (long + i as f64 * cellsize).to_radians())
+            .collect();
+        // For a global grid, the first column is repeated after the last one
+        if (columns as f64 * cellsize - 360.).abs() < 1e-6 {
+            longitudes.push((long + 360.).to_radians());
+            for row in data.iter_mut() {
+                *row = row.iter().chain(&row[..1]).copied().collect();
+            }
+        }
+
+        Ok(Table2D::try_new(
+            (StateVariable::LatitudeGeodetic, &latitudes),
+            (StateVariable::Longitude, &longitudes),
+            &data,
+            Interpolator::Linear,
+        )
+        .expect("Bases are sorted and match the data"))
+    }
+
+    /// Calculate the terrain elevation in m at the geodetic latitude and
+    /// longitude in rad.
+    pub fn elevation(&self, lat: f64, long: f64) -> f64 {
+        let [(lat_min, lat_max), (long_min, long_max)] = self.table.bounds();
+
+        // The longitude can be given in any range
+        let long = long_min + (long - long_min).rem_euclid(TAU);
+
+        if lat < lat_min || lat > lat_max || long > long_max {
+            return 0.;
+        }
+
+        self.table.at(lat, long)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_almost_eq_rel;
+
+    const GRID: &str = "ncols 3
+        nrows 2
+        xllcorner -0.5
+        yllcorner 9.5
+        cellsize 1
+        NODATA_value -9999
+        100 200 -9999
+        0 100 300
+    ";
+
+    #[test]
+    fn elevation() {
+        let terrain = Terrain {
+            file: PathBuf::new(),
+            table: Terrain::parse(GRID).unwrap(),
+        };
+        let elevation =
+            |lat: f64, long: f64| terrain.elevation(lat.to_radians(), long.to_radians());
+
+        // Cell centers
+        assert_almost_eq_rel!(elevation(10., 0.), 0_f64, 1e-12);
+        assert_almost_eq_rel!(elevation(11., 0.), 100_f64, 1e-12);
+        assert_almost_eq_rel!(elevation(10., 2.), 300_f64, 1e-12);
+        assert_almost_eq_rel!(elevation(11., 2.), 0_f64, 1e-12);
+
+        // Interpolation
+        assert_almost_eq_rel!(elevation(10.5, 0.5), 100_f64, 1e-12);
+        assert_almost_eq_rel!(elevation(11., 360.5), 150_f64, 1e-12);
+
+        // Outside of the grid
+        assert_eq!(elevation(12., 1.), 0.);
+        assert_eq!(elevation(10.5, -1.), 0.);
+    }
+
+    #[test]
+    fn global_grid() {
+        let terrain = Terrain {
+            file: PathBuf::new(),
+            table: Terrain::parse(
+                "ncols 4
+                nrows 2
+                xllcenter -135
+                yllcenter 0
+                cellsize 90
+                100 200 300 400
+                100 200 300 400
+                ",
+            )
+            .unwrap(),
+        };
+        let elevation = |long: f64| terrain.elevation(0., long.to_radians());
+
+        // Interpolated between the last and the first column
+        assert_almost_eq_rel!(elevation(180.), 250_f64, 1e-12);
+        assert_almost_eq_rel!(elevation(-180.), 250_f64, 1e-12);
+        assert_almost_eq_rel!(elevation(157.5), 325_f64, 1e-12);
+        assert_almost_eq_rel!(elevation(-157.5), 175_f64, 1e-12);
+        assert_almost_eq_rel!(elevation(-135.), 100_f64, 1e-12);
+    }
+
+    #[test]
+    fn invalid_grid() {
+        assert_eq!(
+            Terrain::parse(&GRID.replace("cellsize 1", "")).unwrap_err(),
+            TerrainError::InvalidHeader("cellsize")
+        );
+        assert_eq!(
+            Terrain::parse(&GRID.replace("0 100 300", "0 100")).unwrap_err(),
+            TerrainError::InvalidLine(8)
+        );
+        assert_eq!(
+            Terrain::parse(&GRID.replace("nrows 2", "nrows 3")).unwrap_err(),
+            TerrainError::InvalidRows(2)
+        );
+    }
+}
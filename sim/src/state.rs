@@ -7,7 +7,8 @@
 //! [`State`].
 
 use nalgebra::{vector, SVector, Vector2, Vector3};
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 /// Represents the simulation state.
 ///
@@ -26,6 +27,14 @@ pub struct State {
     pub altitude: f64,
     /// Geopotential altitude in m (used in atmospheric model).
     pub altitude_geopotential: f64,
+    /// Geodetic latitude in rad.
+    pub latitude_geodetic: f64,
+    /// Longitude in rad.
+    pub longitude: f64,
+    /// Altitude along the surface normal of the planet's oblate surface in m.
+    pub altitude_geodetic: f64,
+    /// Geodetic altitude above the terrain in m.
+    pub altitude_above_ground: f64,
     /// Inertial velocity in m/s.
     pub velocity: Vector3<f64>,
     /// Velocity with respect to the planet in m/s.
@@ -58,12 +67,26 @@ pub struct State {
     pub mach_number: f64,
     /// Dynamic pressure in Pa.
     pub dynamic_pressure: f64,
+    /// Atmospheric dynamic viscosity in Pa s.
+    pub viscosity: f64,
+    /// Reynolds number per unit length in 1/m.
+    pub unit_reynolds_number: f64,
+    /// Reynolds number with the reference length of the vehicle.
+    pub reynolds_number: f64,
+    /// Mean free path of the atmospheric gas in m.
+    pub mean_free_path: f64,
+    /// Knudsen number with the reference length of the vehicle.
+    pub knudsen_number: f64,
     /// Angle-of-attack in rad.
     pub alpha: f64,
     /// Euler-angles in rad in the order: Roll, Yaw, Pitch
     pub euler_angles: [f64; 3],
     /// Engine throttle setting
     pub throttle: f64,
+    /// Time since the earliest ignition of the ignited engines in sec, or
+    /// zero if no engine is ignited. In the tables of an engine, it is the
+    /// time since the ignition of that engine.
+    pub time_since_ignition: f64,
 }
 
 impl State {
@@ -111,8 +134,7 @@ impl State {
 }
 
 /// An enum for choosing a state variable.
-/// Each
-#[derive(Debug, Default, Copy, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Default, Copy, Clone, Deserialize, Serialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum StateVariable {
     /// Simulation time
@@ -138,6 +160,14 @@ pub enum StateVariable {
     Altitude,
     /// Used for the atmospheric model
     AltitudeGeopotential,
+    /// Geodetic latitude
+    LatitudeGeodetic,
+    /// Longitude
+    Longitude,
+    /// Height above surface along the surface normal
+    AltitudeGeodetic,
+    /// Height above the terrain along the surface normal
+    AltitudeAboveGround,
     /// Inertial velocity (X)                                             
     Velocity1,
     /// Inertial velocity (Y)  
@@ -218,6 +248,16 @@ pub enum StateVariable {
     MachNumber,
     /// Dynamic pressure                                              
     DynamicPressure,
+    /// Atmosphere dynamic viscosity
+    Viscosity,
+    /// Reynolds number per unit length
+    UnitReynoldsNumber,
+    /// Reynolds number with the vehicle reference length
+    ReynoldsNumber,
+    /// Mean free path of the atmosphere
+    MeanFreePath,
+    /// Knudsen number with the vehicle reference length
+    KnudsenNumber,
     /// Angle of attack                                               
     Alpha,
     /// Roll angle with respect to launch frame                       
@@ -228,6 +268,8 @@ pub enum StateVariable {
     EulerAnglesPitch,
     /// Computed auto-throttle                                        
     Throttle,
+    /// Time since the ignition of the engines
+    TimeSinceIgnition,
 }
 
 impl StateVariable {
@@ -245,6 +287,10 @@ impl StateVariable {
             StateVariable::PositionPlanet3 => state.position_planet[2],
             StateVariable::Altitude => state.altitude,
             StateVariable::AltitudeGeopotential => state.altitude_geopotential,
+            StateVariable::LatitudeGeodetic => state.latitude_geodetic,
+            StateVariable::Longitude => state.longitude,
+            StateVariable::AltitudeGeodetic => state.altitude_geodetic,
+            StateVariable::AltitudeAboveGround => state.altitude_above_ground,
             StateVariable::Velocity1 => state.velocity[0],
             StateVariable::Velocity2 => state.velocity[1],
             StateVariable::Velocity3 => state.velocity[2],
@@ -285,11 +331,17 @@ impl StateVariable {
             StateVariable::Density => state.density,
             StateVariable::MachNumber => state.mach_number,
             StateVariable::DynamicPressure => state.dynamic_pressure,
+            StateVariable::Viscosity => state.viscosity,
+            StateVariable::UnitReynoldsNumber => state.unit_reynolds_number,
+            StateVariable::ReynoldsNumber => state.reynolds_number,
+            StateVariable::MeanFreePath => state.mean_free_path,
+            StateVariable::KnudsenNumber => state.knudsen_number,
             StateVariable::Alpha => state.alpha,
             StateVariable::EulerAnglesRoll => state.euler_angles[0],
             StateVariable::EulerAnglesYaw => state.euler_angles[1],
             StateVariable::EulerAnglesPitch => state.euler_angles[2],
             StateVariable::Throttle => state.throttle,
+            StateVariable::TimeSinceIgnition => state.time_since_ignition,
         }
     }
 }
@@ -5,22 +5,38 @@
 //! Defines the [`Steering`] struct which handles the vehicles orientation.
 
 use crate::{config::SteeringConfig, state::StateVariable, State};
+use serde::{Serialize, Serializer};
 
 /// Handles the vehicle orientation for each axis.
 ///
 /// It calculates the orientation with cubic polynomials using 4 coefficients
 /// and a chosen state variables.
 /// The coefficients are stored in ascending order: c0 + c1*y + c2*y^2 + c3*y^3
-#[derive(Debug, Default, Clone)]
+///
+/// When serialized, the first coefficient is omitted, so the output matches
+/// the configuration (see [`SteeringConfig`]).
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct Steering {
     /// State variable (unit X) and coefficients in °/X for the roll axis.
+    #[serde(serialize_with = "serialize_axis")]
     roll: (StateVariable, [f64; 4]),
     /// State variable (unit X) and coefficients in °/X for the yaw axis.
+    #[serde(serialize_with = "serialize_axis")]
     yaw: (StateVariable, [f64; 4]),
     /// State variable (unit X) and coefficients in °/X for the pitch axis.
+    #[serde(serialize_with = "serialize_axis")]
     pitch: (StateVariable, [f64; 4]),
 }
 
+/// Serializes an axis without its first coefficient, which is only known
+/// when the phase is running (see [`Steering::init`]).
+fn serialize_axis<S: Serializer>(
+    axis: &(StateVariable, [f64; 4]),
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    (axis.0, &axis.1[1..]).serialize(serializer)
+}
+
 impl Steering {
     /// Updates itself with the new configuration parameters.
     ///
@@ -0,0 +1,209 @@
+//! Defines the [`ThirdBody`] enum, which handles the gravitational
+//! perturbations of the Sun and the Moon.
+//!
+//! They are modelled as point masses. Their positions are calculated with the
+//! analytic low-precision ephemerides from Montenbruck and Gill: "Satellite
+//! Orbits", p. 70 ff. They are accurate to about 0.1-1% in the distance and
+//! a few arcminutes in the direction, which is more than enough for the
+//! perturbations.
+//!
+//! The positions are geocentric, so the perturbations are only valid for the
+//! earth models. They are given in the mean equator and equinox of J2000,
+//! which is only the inertial frame of the simulation if the planet frame is
+//! tied to it by the sidereal time (see [`crate::orientation`]). With the
+//! `aligned` orientation, third bodies are not supported.
+
+use crate::utils::constants::{
+    ARCSECONDS_PER_DEGREE, DAYS_PER_JULIAN_CENTURY, JULIAN_DATE_J2000,
+    MOON_GRAVITATIONAL_PARAMETER, OBLIQUITY_J2000, SUN_GRAVITATIONAL_PARAMETER,
+};
+use nalgebra::{vector, Rotation3, Vector3};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Represents a celestial body, which perturbs the trajectory.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThirdBody {
+    /// The Sun
+    Sun,
+    /// The Moon
+    Moon,
+}
+
+impl ThirdBody {
+    /// Get the gravitational constant in m^3/s^2.
+    fn mu(&self) -> f64 {
+        match self {
+            ThirdBody::Sun => SUN_GRAVITATIONAL_PARAMETER,
+            ThirdBody::Moon => MOON_GRAVITATIONAL_PARAMETER,
+        }
+    }
+
+    /// Calculate the geocentric position in m in the mean equator and equinox
+    /// of J2000, at the Julian date (TT).
+    pub fn position(&self, julian_date: f64) -> Vector3<f64> {
+        // Julian centuries since J2000
+        let t = (julian_date - JULIAN_DATE_J2000) / DAYS_PER_JULIAN_CENTURY;
+
+        let (longitude, latitude, distance) = match self {
+            ThirdBody::Sun => sun_ecliptic(t),
+            ThirdBody::Moon => moon_ecliptic(t),
+        };
+
+        let ecliptic = distance
+            * vector![
+                longitude.cos() * latitude.cos(),
+                longitude.sin() * latitude.cos(),
+                latitude.sin()
+            ];
+
+        Rotation3::from_axis_angle(&Vector3::x_axis(), OBLIQUITY_J2000.to_radians()) * ecliptic
+    }
+
+    /// Calculate the perturbing acceleration in m/s^2 of a vehicle at the
+    /// inertial position in m, at the Julian date (TT).
+    ///
+    /// It is the difference of the attraction of the vehicle and of the earth
+    /// center, as the geocentric frame is accelerated as well.
+    pub fn acceleration(&self, position: Vector3<f64>, julian_date: f64) -> Vector3<f64> {
+        let body = self.position(julian_date);
+        let relative = body - position;
+
+        self.mu() * (relative / relative.norm().powi(3) - body / body.norm().powi(3))
+    }
+}
+
+/// Calculate the ecliptic longitude and latitude in rad and the distance in m
+/// of the Sun, `t` Julian centuries after J2000.
+fn sun_ecliptic(t: f64) -> (f64, f64, f64) {
+    // Mean anomaly
+    let m = (357.5256 + 35999.049 * t).to_radians();
+
+    let longitude = 282.9400
+        + m.to_degrees()
+        + (6892. * m.sin() + 72. * (2. * m).sin()) / ARCSECONDS_PER_DEGREE;
+    let distance = (149.619 - 2.499 * m.cos() - 0.021 * (2. * m).cos()) * 1e9;
+
+    (longitude.to_radians(), 0., distance)
+}
+
+/// Calculate the ecliptic longitude and latitude in rad and the distance in m
+/// of the Moon, `t` Julian centuries after J2000.
+fn moon_ecliptic(t: f64) -> (f64, f64, f64) {
+    // Mean longitude (referred to the equinox of J2000)
+    let l0 = 218.31617 + 481267.88088 * t - 1.3972 * t;
+    // Mean anomaly of the Moon
+    let l = (134.96292 + 477198.86753 * t).to_radians();
+    // Mean anomaly of the Sun
+    let lp = (357.52543 + 35999.04944 * t).to_radians();
+    // Mean distance of the Moon from its ascending node
+    let f = (93.27283 + 483202.01873 * t).to_radians();
+    // Difference between the mean longitudes of the Sun and the Moon
+    let d = (297.85027 + 445267.11135 * t).to_radians();
+
+    let longitude = l0
+        + (22640. * l.sin() + 769. * (2. * l).sin() - 4586. * (l - 2. * d).sin()
+            + 2370. * (2. * d).sin()
+            - 668. * lp.sin()
+            - 412. * (2. * f).sin()
+            - 212. * (2. * l - 2. * d).sin()
+            - 206. * (l + lp - 2. * d).sin()
+            + 192. * (l + 2. * d).sin()
+            - 165. * (lp - 2. * d).sin()
+            + 148. * (l - lp).sin()
+            - 125. * d.sin()
+            - 110. * (l + lp).sin()
+            - 55. * (2. * f - 2. * d).sin())
+            / ARCSECONDS_PER_DEGREE;
+
+    let latitude = (18520.
+        * (f + (longitude - l0).to_radians()
+            + ((412. * (2. * f).sin() + 541. * lp.sin()) / ARCSECONDS_PER_DEGREE).to_radians())
+        .sin()
+        - 526. * (f - 2. * d).sin()
+        + 44. * (l + f - 2. * d).sin()
+        - 31. * (-l + f - 2. * d).sin()
+        - 25. * (-2. * l + f).sin()
+        - 23. * (lp + f - 2. * d).sin()
+        + 21. * (-l + f).sin()
+        + 11. * (-lp + f - 2. * d).sin())
+        / ARCSECONDS_PER_DEGREE;
+
+    let distance = (385000.
+        - 20905. * l.cos()
+        - 3699. * (2. * d - l).cos()
+        - 2956. * (2. * d).cos()
+        - 570. * (2. * l).cos()
+        + 246. * (2. * l - 2. * d).cos()
+        - 205. * (lp - 2. * d).cos()
+        - 171. * (l + 2. * d).cos()
+        - 152. * (l + lp - 2. * d).cos())
+        * 1e3;
+
+    (longitude.to_radians(), latitude.to_radians(), distance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_almost_eq_rel;
+
+    /// Astronomical unit in m.
+    const AU: f64 = 1.495978707e11;
+
+    #[test]
+    fn sun_position() {
+        // 2000-01-01 12:00 TT: longitude 280.38° (without aberration),
+        // distance 0.98333 AU
+        let position = ThirdBody::Sun.position(JULIAN_DATE_J2000);
+
+        let (longitude, _, distance) = sun_ecliptic(0.);
+        assert_almost_eq_rel!(longitude.to_degrees().rem_euclid(360.), 280.38_f64, 0.0002);
+        assert_almost_eq_rel!(distance, 0.98333 * AU, 0.0005);
+
+        // Right ascension 281.29° and declination -23.03°
+        let declination = (position.z / position.norm()).asin().to_degrees();
+        let right_ascension = position.y.atan2(position.x).to_degrees() + 360.;
+        assert_almost_eq_rel!(declination, -23.03_f64, 0.001);
+        assert_almost_eq_rel!(right_ascension, 281.29_f64, 0.0002);
+    }
+
+    #[test]
+    fn moon_position() {
+        // 1992-04-12 0:00 TT, see Meeus: "Astronomical Algorithms", ex. 47.a:
+        // longitude 133.16°, latitude -3.23°, distance 368410 km
+        let t = (2448724.5 - JULIAN_DATE_J2000) / DAYS_PER_JULIAN_CENTURY;
+        let (longitude, latitude, distance) = moon_ecliptic(t);
+
+        // Meeus refers to the equinox of date, which differs by about 0.1°
+        assert_almost_eq_rel!(
+            (longitude.to_degrees() + 1.3972 * t).rem_euclid(360.),
+            133.16_f64,
+            0.0005
+        );
+        assert_almost_eq_rel!(latitude.to_degrees(), -3.23_f64, 0.01);
+        assert_almost_eq_rel!(distance, 3.6841e8_f64, 0.001);
+    }
+
+    #[test]
+    fn acceleration() {
+        let julian_date = JULIAN_DATE_J2000 + 100.;
+
+        // No perturbation at the planet center
+        assert_eq!(
+            ThirdBody::Moon.acceleration(Vector3::zeros(), julian_date),
+            Vector3::zeros()
+        );
+
+        // Tidal acceleration towards the Moon of about 2 mu r / d^3
+        let moon = ThirdBody::Moon.position(julian_date);
+        let position = 7e6 * moon.normalize();
+        let acceleration = ThirdBody::Moon.acceleration(position, julian_date);
+        assert_almost_eq_rel!(
+            acceleration.dot(&moon.normalize()),
+            2. * MOON_GRAVITATIONAL_PARAMETER * 7e6 / moon.norm().powi(3),
+            0.03
+        );
+    }
+}
@@ -9,7 +9,9 @@ use nalgebra::{matrix, Rotation3};
 
 /// Calculates the transformation matrix from inertial frame to the launch frame.
 ///
-/// Uses the geocentric latitude, longitude and azimuth in rad.
+/// Uses the geocentric latitude, longitude and azimuth in rad. If the planet
+/// frame is not aligned with the inertial frame, this is the transformation
+/// from the planet frame at launch.
 pub fn inertial_to_launch(lat: f64, long: f64, az: f64) -> Rotation3<f64> {
     Rotation3::from_matrix(&matrix![
                                        lat.cos()*long.cos(),                                lat.cos()*long.sin(),           lat.sin();
@@ -29,26 +31,15 @@ pub fn launch_to_body(roll: f64, yaw: f64, pitch: f64) -> Rotation3<f64> {
     ])
 }
 
-/// Calculates the transformation matrix from launch frame to body frame.
-///
-/// Uses the launch geocentric latitude, longitude and azimuth in rad, as well as the
-/// euler angles in rad in the order: Roll, Yaw, Pitch.
+/// Calculates the transformation matrix from inertial frame to body frame.
 ///
-/// This function calls [`launch_to_body`] and [`inertial_to_launch`].
-pub fn inertial_to_body(launch: [f64; 3], euler_angles: [f64; 3]) -> Rotation3<f64> {
-    launch_to_body(euler_angles[0], euler_angles[1], euler_angles[2])
-        * inertial_to_launch(launch[0], launch[1], launch[2])
-}
-
-/// Calculates the transformation matrix from inertial frame to planet relative
-/// frame.
+/// Uses the transformation matrix from inertial frame to launch frame, as well
+/// as the euler angles in rad in the order: Roll, Yaw, Pitch.
 ///
-/// Uses the current simulation time in sec and the planet's rotation rate in
-/// rad/sec.
-pub fn inertial_to_planet(time: f64, rotation_rate: f64) -> Rotation3<f64> {
-    Rotation3::from_matrix(&matrix![
-        (rotation_rate*time).cos(), (rotation_rate*time).sin(), 0.;
-       -(rotation_rate*time).sin(), (rotation_rate*time).cos(), 0.;
-                                0.,                         0., 1.
-    ])
+/// This function calls [`launch_to_body`].
+pub fn inertial_to_body(
+    inertial_to_launch: &Rotation3<f64>,
+    euler_angles: [f64; 3],
+) -> Rotation3<f64> {
+    launch_to_body(euler_angles[0], euler_angles[1], euler_angles[2]) * inertial_to_launch
 }
@@ -17,12 +17,36 @@ pub const PASCAL_PER_PSF: f64 = KILOGRAM_PER_POUND * STD_GRAVITY / SQUARE_METER_
 
 #[cfg(test)]
 pub const KILOGRAM_PER_SLUG: f64 = 1.459390e+01;
-#[cfg(test)]
 pub const NEWTON_PER_POUND_FORCE: f64 = KILOGRAM_PER_POUND * STD_GRAVITY;
 
 // CONSTANTS from [3] p. IV-7
 pub const STD_GRAVITY: f64 = 9.80665; // [m / s^2]
-const AIR_MOLECULAR_WEIGHT: f64 = 28.9644; // [g / mol]
-const GAS_CONSTANT: f64 = 8.31432e3; // [J / kmol K]
+pub const AIR_MOLECULAR_WEIGHT: f64 = 28.9644; // [g / mol]
+pub const GAS_CONSTANT: f64 = 8.31432e3; // [J / kmol K]
 pub const AIR_KAPPA: f64 = 1.40; // [-]
 pub const AIR_GAS_CONSTANT: f64 = GAS_CONSTANT / AIR_MOLECULAR_WEIGHT; // [J / kg K]
+
+// CONSTANTS from the U.S. Standard Atmosphere, 1976, p. 8
+pub const US76_EARTH_RADIUS: f64 = 6.356766e6; // [m]
+pub const AIR_SUTHERLAND_CONSTANT: f64 = 1.458e-6; // [kg / m s K^0.5]
+pub const AIR_SUTHERLAND_TEMPERATURE: f64 = 110.4; // [K]
+
+// CONSTANTS of carbon dioxide (main component of the Mars and Venus atmosphere)
+const CO2_MOLECULAR_WEIGHT: f64 = 44.01; // [g / mol]
+pub const CO2_KAPPA: f64 = 1.29; // [-]
+pub const CO2_GAS_CONSTANT: f64 = GAS_CONSTANT / CO2_MOLECULAR_WEIGHT; // [J / kg K]
+
+// CONSTANTS of carbon dioxide from White: "Viscous Fluid Flow", 3rd ed., p. 28
+pub const CO2_SUTHERLAND_CONSTANT: f64 = 1.503e-6; // [kg / m s K^0.5]
+pub const CO2_SUTHERLAND_TEMPERATURE: f64 = 222.; // [K]
+
+// CONSTANTS from Montenbruck and Gill: "Satellite Orbits", p. 70 ff.
+pub const SUN_GRAVITATIONAL_PARAMETER: f64 = 1.32712440018e20; // [m^3 / s^2]
+pub const MOON_GRAVITATIONAL_PARAMETER: f64 = 4.902801e12; // [m^3 / s^2]
+pub const OBLIQUITY_J2000: f64 = 23.43929111; // [°]
+pub const ARCSECONDS_PER_DEGREE: f64 = 3600.;
+
+// Time
+pub const JULIAN_DATE_J2000: f64 = 2451545.0; // [d]
+pub const DAYS_PER_JULIAN_CENTURY: f64 = 36525.;
+pub const SECONDS_PER_DAY: f64 = 86400.;
@@ -1,8 +1,9 @@
-//! This module contains some constants used throughout the crate, and the
-//! tables used for the aerodynamic coefficients.
+//! This module contains some constants used throughout the crate, the tables
+//! used for the aerodynamic coefficients and reproducible random numbers.
 
 pub mod constants;
 mod macros;
+pub mod random;
 mod tables;
 
-pub use tables::Table;
+pub use tables::{Interpolator, Table, Table1D, Table2D};
@@ -0,0 +1,55 @@
+//! Defines reproducible random numbers. The numbers are only determined by a
+//! seed and an index, so they do not depend on the order in which they are
+//! drawn.
+//!
+//! Uses the SplitMix64 generator, see Steele et al.: "Fast Splittable
+//! Pseudorandom Number Generators", 2014.
+
+use std::f64::consts::TAU;
+
+/// Mixes the bits of the value with the SplitMix64 finalizer.
+fn split_mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+/// Returns a uniformly distributed random number in (0, 1) for the seed and
+/// index.
+pub fn uniform(seed: u64, index: u64) -> f64 {
+    let bits = split_mix(split_mix(seed) ^ index.wrapping_mul(0xD1B54A32D192ED03));
+    // Use the upper 53 bits, shifted by half a step to exclude 0
+    ((bits >> 11) as f64 + 0.5) / (1_u64 << 53) as f64
+}
+
+/// Returns a normally distributed random number with zero mean and unit
+/// standard deviation for the seed and index.
+///
+/// Uses the Box-Muller transform.
+pub fn normal(seed: u64, index: u64) -> f64 {
+    let u1 = uniform(seed, 2 * index);
+    let u2 = uniform(seed, 2 * index + 1);
+    f64::sqrt(-2. * u1.ln()) * (TAU * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normal_distribution() {
+        const SAMPLES: u64 = 100_000;
+
+        let samples: Vec<f64> = (0..SAMPLES).map(|i| normal(42, i)).collect();
+        let mean = samples.iter().sum::<f64>() / SAMPLES as f64;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / SAMPLES as f64;
+
+        assert!(mean.abs() < 0.01);
+        assert!((variance - 1.).abs() < 0.01);
+
+        // Reproducible, but different for other seeds
+        assert_eq!(normal(42, 7), samples[7]);
+        assert_ne!(normal(43, 7), samples[7]);
+    }
+}
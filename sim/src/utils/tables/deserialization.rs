@@ -8,19 +8,45 @@
 //! their `try_new` methods.
 //!
 //! The deserialization is handled automatically with serde's `derive`.
+//! The JSON Schema of the tables is also the one of the unchecked tables.
 //!
 //! Refer to the [`Table1D`], [`Table2D`] and [`Table3D`] implementations to
 //! learn more about the fields of each struct.
 
 use super::init::TableInitError;
 use super::*;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
 use serde::Deserialize;
 
-#[derive(Deserialize)]
+/// Implements [`JsonSchema`] for a table by using the schema of its unchecked
+/// counterpart, as `schemars` ignores serde's `try_from` attribute.
+macro_rules! impl_json_schema {
+    ($table: ty, $unchecked: ty) => {
+        impl JsonSchema for $table {
+            fn schema_name() -> String {
+                stringify!($table).into()
+            }
+
+            fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+                <$unchecked>::json_schema(gen)
+            }
+        }
+    };
+}
+
+impl_json_schema!(Table1D, Table1DUnchecked);
+impl_json_schema!(Table2D, Table2DUnchecked);
+impl_json_schema!(Table3D, Table3DUnchecked);
+
+#[derive(Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Table1DUnchecked {
+    /// The state variable and its bases to interpolate with.
     x: (StateVariable, Box<[f64]>),
+    /// The table data. One entry corresponds to each state variable base.
     data: Box<[f64]>,
+    /// The type of interpolation.
     #[serde(default)]
     interpolator: Interpolator,
 }
@@ -33,12 +59,17 @@ impl TryFrom<Table1DUnchecked> for Table1D {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Table2DUnchecked {
+    /// The first state variable and its bases to interpolate with.
     x: (StateVariable, Box<[f64]>),
+    /// The second state variable and its bases to interpolate with.
     y: (StateVariable, Box<[f64]>),
+    /// The table data as array of arrays, indexed by the first and second
+    /// state variable bases.
     data: Box<[Box<[f64]>]>,
+    /// The type of interpolation.
     #[serde(default)]
     interpolator: Interpolator,
 }
@@ -56,14 +87,20 @@ impl TryFrom<Table2DUnchecked> for Table2D {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Table3DUnchecked {
+    /// The first state variable and its bases to interpolate with.
     x: (StateVariable, Box<[f64]>),
+    /// The second state variable and its bases to interpolate with.
     y: (StateVariable, Box<[f64]>),
+    /// The third state variable and its bases to interpolate with.
     z: (StateVariable, Box<[f64]>),
+    /// The table data as array of arrays of arrays, indexed by the first,
+    /// second and third state variable bases.
     #[allow(clippy::type_complexity)]
     data: Box<[Box<[Box<[f64]>]>]>,
+    /// The type of interpolation.
     #[serde(default)]
     interpolator: Interpolator,
 }
@@ -9,7 +9,8 @@ mod deserialization;
 mod linear_interpolation;
 
 use crate::state::{State, StateVariable};
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
 /// Represents a table.
@@ -17,7 +18,7 @@ use std::fmt::Debug;
 /// The interpolated needs to be an enum (instead of a trait), so that it can
 /// be deserialized into any table, depending on user input.
 /// Sadly, untagged enums swallow precise error messages.
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, PartialEq)]
 #[serde(untagged)]
 pub enum Table {
     /// A 1D table which is interpolated with one state variable.
@@ -45,6 +46,17 @@ impl Table {
             Self::D3(table) => table.at_state(state),
         }
     }
+
+    /// Returns whether the table is interpolated with the state variable.
+    pub fn depends_on(&self, variable: StateVariable) -> bool {
+        match self {
+            Self::D1(table) => table.x.0 == variable,
+            Self::D2(table) => table.x.0 == variable || table.y.0 == variable,
+            Self::D3(table) => {
+                table.x.0 == variable || table.y.0 == variable || table.z.0 == variable
+            }
+        }
+    }
 }
 
 /// Represents a 1D table.
@@ -52,7 +64,7 @@ impl Table {
 /// It is interpolated with one state variable, specified in the `"x"` field.
 ///
 /// The deserialization is handled with [`deserialization::Table1DUnchecked`].
-#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(try_from = "deserialization::Table1DUnchecked")]
 pub struct Table1D {
     /// The state variable and its bases to interpolate with.
@@ -69,7 +81,7 @@ pub struct Table1D {
 /// `"y"` fields.
 ///
 /// The deserialization is handled with [`deserialization::Table2DUnchecked`].
-#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(try_from = "deserialization::Table2DUnchecked")]
 pub struct Table2D {
     /// The first state variable and its bases to interpolate with.
@@ -90,7 +102,7 @@ pub struct Table2D {
 /// and `"z"` fields.
 ///
 /// The deserialization is handled with [`deserialization::Table3DUnchecked`].
-#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(try_from = "deserialization::Table3DUnchecked")]
 pub struct Table3D {
     /// The first state variable and its bases to interpolate with.
@@ -113,7 +125,7 @@ pub struct Table3D {
 ///
 /// For now only includes linear interpolation, but cubic interpolation
 /// can be added in the future.
-#[derive(Debug, Default, Clone, Copy, Deserialize, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, JsonSchema, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum Interpolator {
     #[default]
@@ -149,16 +161,29 @@ mod interpolate {
         ///
         /// Linear interpolation uses [`linear_interpolation::bilinear_interpolate`].
         pub fn at_state(&self, state: &State) -> f64 {
+            self.at(self.x.0.get_value(state), self.y.0.get_value(state))
+        }
+
+        /// Interpolate the table data with the values of the two state
+        /// variables.
+        ///
+        /// Linear interpolation uses [`linear_interpolation::bilinear_interpolate`].
+        pub fn at(&self, x: f64, y: f64) -> f64 {
             match self.interpolator {
-                Interpolator::Linear => {
-                    let x = self.x.0.get_value(state);
-                    let y = self.y.0.get_value(state);
-                    linear_interpolation::bilinear_interpolate(
-                        &self.x.1, x, &self.y.1, y, &self.data,
-                    )
-                }
+                Interpolator::Linear => linear_interpolation::bilinear_interpolate(
+                    &self.x.1, x, &self.y.1, y, &self.data,
+                ),
             }
         }
+
+        /// Returns the range of the bases of the first and second state
+        /// variable.
+        pub fn bounds(&self) -> [(f64, f64); 2] {
+            [
+                (self.x.1[0], self.x.1[self.x.1.len() - 1]),
+                (self.y.1[0], self.y.1[self.y.1.len() - 1]),
+            ]
+        }
     }
 
     impl Table3D {
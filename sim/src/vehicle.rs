@@ -6,11 +6,12 @@
 //! regarding the vehicle.
 
 use crate::config::VehicleConfig;
-use crate::state::State;
+use crate::state::{State, StateVariable};
 use crate::utils::constants::{NEARLY_ZERO, STD_GRAVITY};
 use crate::utils::Table;
 use nalgebra::{vector, Vector3};
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::f64::consts::PI;
 
 /// Calculates the third side of a triangle using two sides and
@@ -52,14 +53,18 @@ fn side_side_angle(a: f64, b: f64, alpha: f64) -> Option<f64> {
 ///
 /// Its method use the parameters together with some state variables to
 /// calculate derived state variables.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct Vehicle {
     /// Mass of the vehicle without propellant in kg.
     pub structure_mass: f64,
     /// Initial mass of the propellant in kg.
+    #[serde(rename = "propellant_mass")]
     pub initial_propellant_mass: f64,
     /// Reference area of the vehicle in m^2. Used for aerodynamic calculations.
     reference_area: f64,
+    /// Reference length of the vehicle in m. Used for the Reynolds and
+    /// Knudsen number.
+    reference_length: f64,
     /// Table used to calculate the drag coefficients.
     drag_coeff: Table,
     /// Table used to calculate the lift coefficients.
@@ -68,6 +73,11 @@ pub struct Vehicle {
     side_force_coeff: Table,
     /// Engines of the vehicle.
     engines: Vec<Engine>,
+    /// Remaining stages of the vehicle. The first one is the active stage.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stages: Vec<Stage>,
+    /// Behavior when the propellant is depleted.
+    pub burnout: Burnout,
 }
 
 impl Vehicle {
@@ -82,6 +92,9 @@ impl Vehicle {
         if let Some(config) = config.reference_area {
             self.reference_area = config;
         }
+        if let Some(config) = config.reference_length {
+            self.reference_length = config;
+        }
         if let Some(config) = &config.drag_coeff {
             self.drag_coeff = config.clone();
         }
@@ -94,6 +107,78 @@ impl Vehicle {
         if let Some(config) = &config.engines {
             self.engines.clone_from(config);
         }
+        if let Some(config) = config.burnout {
+            self.burnout = config;
+        }
+        if let Some(config) = &config.stages {
+            self.stages.clone_from(config);
+            self.apply_stage();
+        }
+        if let Some(config) = &config.engine_states {
+            // The engines of the remaining stages keep the state until they
+            // are active. The names are checked by the config validation, so
+            // unknown names belong to separated stages.
+            let engines = self
+                .engines
+                .iter_mut()
+                .chain(self.stages.iter_mut().flat_map(|stage| &mut stage.engines));
+            for engine in engines {
+                if let Some(state) = engine.name.as_ref().and_then(|name| config.get(name)) {
+                    engine.update_with_state(state);
+                }
+            }
+        }
+    }
+
+    /// Applies the active stage. The structure mass includes the upper
+    /// stages, so the propellant mass of the state is the one of the active
+    /// stage.
+    fn apply_stage(&mut self) {
+        let Some(stage) = self.stages.first() else {
+            return;
+        };
+
+        self.structure_mass =
+            stage.structure_mass + self.stages[1..].iter().map(Stage::mass).sum::<f64>();
+        self.initial_propellant_mass = stage.propellant_mass;
+        self.engines.clone_from(&stage.engines);
+        if let Some(reference_area) = stage.reference_area {
+            self.reference_area = reference_area;
+        }
+        if let Some(drag_coeff) = &stage.drag_coeff {
+            self.drag_coeff = drag_coeff.clone();
+        }
+        if let Some(lift_coeff) = &stage.lift_coeff {
+            self.lift_coeff = lift_coeff.clone();
+        }
+        if let Some(side_force_coeff) = &stage.side_force_coeff {
+            self.side_force_coeff = side_force_coeff.clone();
+        }
+    }
+
+    /// Returns the initial mass of the vehicle in kg.
+    pub fn initial_mass(&self) -> f64 {
+        self.structure_mass + self.initial_propellant_mass
+    }
+
+    /// Returns whether there is a stage below the last stage, which can be
+    /// separated.
+    pub fn can_separate(&self) -> bool {
+        self.stages.len() > 1
+    }
+
+    /// Separates the active stage together with its remaining propellant and
+    /// applies the next stage. Returns the mass of the remaining stages in kg,
+    /// or `None` if there is no stage to separate.
+    pub fn separate_stage(&mut self) -> Option<f64> {
+        if !self.can_separate() {
+            return None;
+        }
+
+        self.stages.remove(0);
+        self.apply_stage();
+
+        Some(self.initial_mass())
     }
 }
 
@@ -111,33 +196,80 @@ impl Vehicle {
         f64::atan(velocity.z / velocity.x)
     }
 
-    /// Calculates the thrust force of the vehicle at the current throttle
-    /// and atmospheric pressure.
+    /// Calculates the thrust force of the vehicle at the throttle and the
+    /// state, e.g. the atmospheric pressure.
     ///
     /// The function simply adds up the thrust of each engine.
-    pub fn thrust_force(&self, throttle: f64, pressure_atmos: f64) -> Vector3<f64> {
-        throttle
-            * self
-                .engines
-                .iter()
-                .map(|eng| eng.thrust(pressure_atmos))
-                .sum::<Vector3<f64>>()
+    pub fn thrust_force(&self, throttle: f64, state: &State) -> Vector3<f64> {
+        self.engines
+            .iter()
+            .map(|eng| eng.thrust(throttle, state))
+            .sum::<Vector3<f64>>()
     }
 
-    /// Calculates the massflow of the vehicle at the current throttle.
+    /// Calculates the massflow of the vehicle at the throttle and the state.
     ///
     /// The function simply adds up the massflow of each engine.
-    pub fn massflow(&self, throttle: f64) -> f64 {
-        throttle * self.engines.iter().map(|eng| eng.massflow()).sum::<f64>()
+    pub fn massflow(&self, throttle: f64, state: &State) -> f64 {
+        self.engines
+            .iter()
+            .map(|eng| eng.massflow(throttle, state))
+            .sum::<f64>()
+    }
+
+    /// Returns the time since the earliest ignition of the ignited engines in
+    /// sec, or zero if no engine is ignited.
+    pub fn time_since_ignition(&self, state: &State) -> f64 {
+        self.engines
+            .iter()
+            .filter(|engine| engine.ignited)
+            .map(|engine| engine.time_since_ignition(state))
+            .fold(0., f64::max)
+    }
+
+    /// Returns the time in sec until the next ignited engine reaches its burn
+    /// time, or `None` if no engine will burn out.
+    pub fn burn_time_to_go(&self, state: &State) -> Option<f64> {
+        self.engines
+            .iter()
+            .filter_map(|engine| engine.burn_time_to_go(state))
+            .reduce(f64::min)
+    }
+
+    /// Shuts down the engines, which reached their burn time. Returns whether
+    /// an engine burned out.
+    pub fn burn_out(&mut self, state: &State) -> bool {
+        let mut burned_out = false;
+        for engine in self.engines.iter_mut() {
+            if engine
+                .burn_time_to_go(state)
+                .is_some_and(|time| time < NEARLY_ZERO)
+            {
+                engine.burned_out = true;
+                burned_out = true;
+            }
+        }
+        burned_out
+    }
+
+    /// Sets the ignition time of the ignited engines, which were not ignited
+    /// before, to the simulation time `time` in sec.
+    pub fn ignite(&mut self, time: f64) {
+        for engine in self.engines.iter_mut() {
+            if engine.ignited && engine.ignition_time.is_none() {
+                engine.ignition_time = Some(time);
+            }
+        }
     }
 
     /// Calculates the throttle setting to stay within the specified maximum
-    /// acceleration.
+    /// acceleration. Uses the mass, the aerodynamic force and the state for
+    /// the thrust.
     ///
     /// The function first gathers the maximum possible thrust. It then uses
     /// the [`side-side-angle`] algorithm to figure out the required thrust to
     /// reach the maximum allowed acceleration. Lastly it will clamp the
-    /// throttle to be between 0 and 1.
+    /// throttle to be between 0 and the commanded throttle `max_throttle`.
     ///
     /// __Attention:__ The function does not guarantee that the throttle will
     /// lead to an allowed acceleration, because it clamps the throttle.
@@ -145,18 +277,17 @@ impl Vehicle {
     /// the allowed acceleration if it compensates the big aerodynamic
     /// acceleration with a huge thrust. As the throttle needs to be clamped,
     /// it could have resulted in an invalid acceleration.
-    pub fn auto_throttle(
-        &self,
-        max_acceleration: f64,
-        mass: f64,
-        pressure_atmos: f64,
-        aero: Vector3<f64>,
-    ) -> f64 {
-        let max_thrust = self.thrust_force(1., pressure_atmos);
+    ///
+    /// If the thrust depends on the throttle by tables, the solution is only
+    /// approximate, as the thrust at full throttle is assumed to scale
+    /// linearly.
+    pub fn auto_throttle(&self, max_acceleration: f64, max_throttle: f64, state: &State) -> f64 {
+        let (mass, aero) = (state.mass, state.aero_force_body);
+        let max_thrust = self.thrust_force(1., state);
 
         if max_thrust == Vector3::zeros() {
             // We cannot generate thrust
-            return 1.;
+            return max_throttle;
         }
 
         let angle = PI - aero.angle(&max_thrust);
@@ -167,11 +298,50 @@ impl Vehicle {
         match opt_req_thrust {
             // The clamping can lead to a throttle which violates the maximum acceleration
             // e.g. if the aero forces are very big
-            Some(req_thrust) => (req_thrust / max_thrust.norm()).clamp(0., 1.),
-            None => 1.,
+            Some(req_thrust) => (req_thrust / max_thrust.norm()).clamp(0., max_throttle),
+            None => max_throttle,
         }
     }
 
+    /// Calculates the throttle setting at which the sensed acceleration along
+    /// the body x-axis reaches `max_axial_acceleration`.
+    ///
+    /// Returns infinity if the thrust has no axial component.
+    pub fn axial_throttle(&self, max_axial_acceleration: f64, state: &State) -> f64 {
+        let max_thrust = self.thrust_force(1., state).x;
+        if max_thrust <= 0. {
+            return f64::INFINITY;
+        }
+
+        ((max_axial_acceleration * state.mass - state.aero_force_body.x) / max_thrust).max(0.)
+    }
+
+    /// Calculates the throttle setting at which the ratio of the thrust to the
+    /// weight is `thrust_to_weight`. The weight uses the magnitude of the
+    /// gravity acceleration.
+    ///
+    /// Returns infinity if the vehicle cannot generate thrust.
+    pub fn thrust_to_weight_throttle(&self, thrust_to_weight: f64, state: &State) -> f64 {
+        let max_thrust = self.thrust_force(1., state).norm();
+        if max_thrust == 0. {
+            return f64::INFINITY;
+        }
+
+        thrust_to_weight * state.mass * state.gravity_acceleration.norm() / max_thrust
+    }
+
+    /// Calculates the Reynolds number with the reference length, using the
+    /// unit Reynolds number of the state.
+    pub fn reynolds_number(&self, state: &State) -> f64 {
+        state.unit_reynolds_number * self.reference_length
+    }
+
+    /// Calculates the Knudsen number with the reference length, using the
+    /// mean free path of the state.
+    pub fn knudsen_number(&self, state: &State) -> f64 {
+        state.mean_free_path / self.reference_length
+    }
+
     /// Calculates the aerodynamic force on the vehicle.
     ///
     /// The function requires the complete state, as the aerodynamic
@@ -198,34 +368,290 @@ impl Vehicle {
 }
 
 /// Represents an engine of the vehicle.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Engine {
+    /// Name of the engine, used to change its state in later phases.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    /// Whether the engine is ignited. Defaults to `true`.
+    #[serde(default = "ignited")]
+    ignited: bool,
+    /// Throttle of the engine relative to the throttle of the vehicle.
+    /// Defaults to 1.
+    #[serde(default = "relative_throttle")]
+    throttle: f64,
     /// Thrust vector incidence (angle between thrust vector and body frame)
     /// in rad. First entry is Pitch, second is Yaw.
     incidence: [f64; 2],
-    /// Vacuum thrust in N.
-    thrust_vac: f64,
-    /// Specific impulse in sec.
-    isp_vac: f64,
+    /// Vacuum thrust in N at full throttle, either constant or a table, e.g.
+    /// a thrust curve over the `"time_since_ignition"`. It is scaled with the
+    /// throttle, unless the table is interpolated with the `"throttle"`, which
+    /// then gives the vacuum thrust at the throttle of the engine. Negative
+    /// values are treated as zero.
+    thrust_vac: EngineParameter,
+    /// Vacuum specific impulse in sec, either constant or a table.
+    isp_vac: EngineParameter,
     /// Exit area in m^2
     exit_area: f64,
+    /// Burn time in sec, after which the engine produces no thrust, e.g. for
+    /// solid rocket motors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    burn_time: Option<f64>,
+    /// Simulation time of the ignition in sec.
+    #[serde(skip)]
+    ignition_time: Option<f64>,
+    /// Whether the engine reached its burn time during the simulation.
+    #[serde(skip)]
+    burned_out: bool,
+}
+
+/// Represents a parameter of an engine, which is either constant or given by
+/// a table.
+///
+/// The tables can use the throttle of the engine and the time since its
+/// ignition.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum EngineParameter {
+    /// A constant value.
+    Constant(f64),
+    /// A table of one to three state variables.
+    Table(Table),
+}
+
+/// Helper function for the default of [`Engine::ignited`].
+fn ignited() -> bool {
+    true
+}
+
+/// Helper function for the default of [`Engine::throttle`].
+fn relative_throttle() -> f64 {
+    1.
 }
 
 impl Engine {
+    /// Returns the name of the engine.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the time since the ignition in sec, or zero if the engine is
+    /// not ignited.
+    fn time_since_ignition(&self, state: &State) -> f64 {
+        match self.ignition_time {
+            Some(ignition_time) => state.time - ignition_time,
+            None => 0.,
+        }
+    }
+
+    /// Returns the time in sec until the engine reaches its burn time, or
+    /// `None` if it is not ignited, already burned out or has no burn time.
+    fn burn_time_to_go(&self, state: &State) -> Option<f64> {
+        if !self.ignited || self.burned_out || self.ignition_time.is_none() {
+            return None;
+        }
+        self.burn_time
+            .map(|burn_time| burn_time - self.time_since_ignition(state))
+    }
+
+    /// Returns the throttle of the engine at the throttle of the vehicle,
+    /// which is zero if the engine is shut down or burned out.
+    ///
+    /// During the simulation, the engine is burned out by the phase at the
+    /// end of the time step, which reaches the burn time. So the burn time is
+    /// only checked with a tolerance, to keep the thrust within the step.
+    fn engine_throttle(&self, throttle: f64, state: &State) -> f64 {
+        let burned_out = self.burned_out
+            || self
+                .burn_time
+                .is_some_and(|burn_time| self.time_since_ignition(state) > burn_time + NEARLY_ZERO);
+        if !self.ignited || burned_out {
+            return 0.;
+        }
+        throttle * self.throttle
+    }
+
+    /// Returns the value of the parameter at the engine throttle. Tables are
+    /// interpolated with the throttle and time since ignition of the engine.
+    fn parameter(&self, parameter: &EngineParameter, throttle: f64, state: &State) -> f64 {
+        match parameter {
+            EngineParameter::Constant(value) => *value,
+            EngineParameter::Table(table) => table.at_state(&State {
+                throttle,
+                time_since_ignition: self.time_since_ignition(state),
+                ..state.clone()
+            }),
+        }
+    }
+
+    /// Returns the vacuum thrust at the engine throttle. It is only scaled
+    /// with the throttle, if it is not interpolated with the throttle.
+    fn thrust_vac(&self, throttle: f64, state: &State) -> f64 {
+        let thrust_vac = self.parameter(&self.thrust_vac, throttle, state).max(0.);
+        match &self.thrust_vac {
+            EngineParameter::Table(table) if table.depends_on(StateVariable::Throttle) => {
+                thrust_vac
+            }
+            _ => throttle * thrust_vac,
+        }
+    }
+
     /// Calculates the thrust vector using the vacuum thrust, exit area,
-    /// atmospheric pressure and incidence angles.
-    fn thrust(&self, pressure_atmos: f64) -> Vector3<f64> {
+    /// atmospheric pressure, incidence angles and the engine throttle.
+    fn thrust(&self, throttle: f64, state: &State) -> Vector3<f64> {
+        let throttle = self.engine_throttle(throttle, state);
+        if throttle == 0. {
+            return Vector3::zeros();
+        }
+
         vector![
             self.incidence[1].cos() * self.incidence[0].cos(),
             self.incidence[1].sin(),
             self.incidence[1].cos() * self.incidence[0].sin()
-        ] * (self.thrust_vac - self.exit_area * pressure_atmos)
+        ] * (self.thrust_vac(throttle, state) - throttle * self.exit_area * state.pressure)
     }
 
-    /// Calculates the massflow using the vacuum thrust and specific impulse.
-    fn massflow(&self) -> f64 {
-        -self.thrust_vac / self.isp_vac / STD_GRAVITY
+    /// Calculates the massflow using the vacuum thrust, specific impulse and
+    /// the engine throttle.
+    fn massflow(&self, throttle: f64, state: &State) -> f64 {
+        let throttle = self.engine_throttle(throttle, state);
+        if throttle == 0. {
+            return 0.;
+        }
+
+        let isp_vac = self.parameter(&self.isp_vac, throttle, state);
+        -self.thrust_vac(throttle, state) / isp_vac / STD_GRAVITY
+    }
+
+    /// Updates the ignition and relative throttle of the engine. A shut down
+    /// engine is ignited again with [`Vehicle::ignite`].
+    fn update_with_state(&mut self, state: &EngineState) {
+        if let Some(ignited) = state.ignited {
+            self.ignited = ignited;
+            if !ignited {
+                self.ignition_time = None;
+                self.burned_out = false;
+            }
+        }
+        if let Some(throttle) = state.throttle {
+            self.throttle = throttle;
+        }
+    }
+}
+
+/// Represents a change of the state of a named engine, e.g. an engine failure.
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct EngineState {
+    /// Whether the engine is ignited.
+    pub ignited: Option<bool>,
+    /// Throttle of the engine relative to the throttle of the vehicle.
+    pub throttle: Option<f64>,
+}
+
+/// Represents the commanded throttle. The auto-throttle limits it to stay
+/// within the maximum acceleration. Negative values are treated as zero.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Throttle {
+    /// Polynomial of a state variable with the coefficients in ascending
+    /// order: c0 + c1*x + c2*x^2 + ...
+    Polynomial(StateVariable, Vec<f64>),
+    /// Table of one to three state variables.
+    Table(Table),
+}
+
+impl Default for Throttle {
+    /// The default throttle is constantly 1.
+    fn default() -> Self {
+        Self::Polynomial(StateVariable::Time, vec![1.])
+    }
+}
+
+impl Throttle {
+    /// Calculates the commanded throttle at the state.
+    pub fn at_state(&self, state: &State) -> f64 {
+        let throttle = match self {
+            Self::Polynomial(variable, coeffs) => {
+                let x = variable.get_value(state);
+                coeffs.iter().rev().fold(0., |acc, coeff| acc * x + coeff)
+            }
+            Self::Table(table) => table.at_state(state),
+        };
+        throttle.max(0.)
+    }
+}
+
+/// Represents additional limits of the throttle. The lowest throttle of all
+/// limits is used.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ThrottleLimits {
+    /// Maximum dynamic pressure in Pa. The throttle is reduced, so that the
+    /// dynamic pressure approaches the limit with a time constant of 1 sec.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_dynamic_pressure: Option<f64>,
+    /// Maximum sensed acceleration along the body x-axis in m/s^2.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_axial_acceleration: Option<f64>,
+    /// Ratio of the thrust to the weight, which is not exceeded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thrust_to_weight: Option<f64>,
+}
+
+/// Represents the behavior when the propellant is depleted. Stages are
+/// separated in either case, only the propellant of the last stage is
+/// considered.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Burnout {
+    /// Cut off the engines, so the thrust and massflow are zero.
+    #[default]
+    Cutoff,
+    /// Stop the simulation with an error, unless the phase ends with the
+    /// burnout.
+    Error,
+}
+
+/// Represents a stage of the vehicle. Stages are separated from the bottom,
+/// either when their propellant is depleted or on an event.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Stage {
+    /// Mass of the stage without propellant in kg.
+    structure_mass: f64,
+    /// Initial mass of the propellant in kg.
+    propellant_mass: f64,
+    /// Engines of the stage.
+    engines: Vec<Engine>,
+    /// Reference area in m^2 while the stage is active. If not set, the one of
+    /// the vehicle is kept.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reference_area: Option<f64>,
+    /// Table used to calculate the drag coefficients while the stage is
+    /// active. If not set, the one of the vehicle is kept.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    drag_coeff: Option<Table>,
+    /// Table used to calculate the lift coefficients while the stage is
+    /// active. If not set, the one of the vehicle is kept.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lift_coeff: Option<Table>,
+    /// Table used to calculate the side-force coefficients while the stage is
+    /// active. If not set, the one of the vehicle is kept.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    side_force_coeff: Option<Table>,
+}
+
+impl Stage {
+    /// Returns the initial mass of the stage in kg.
+    fn mass(&self) -> f64 {
+        self.structure_mass + self.propellant_mass
+    }
+
+    /// Returns the engines of the stage.
+    pub fn engines(&self) -> &[Engine] {
+        &self.engines
     }
 }
 
@@ -233,9 +659,9 @@ impl Engine {
 mod tests {
     use super::*;
     use crate::assert_almost_eq_rel;
-    use crate::config::PhaseConfig;
+    use crate::config::{self, PhaseConfig};
     use crate::example_data::DATA_POINTS;
-    use crate::transformations::inertial_to_body;
+    use crate::transformations::{inertial_to_body, inertial_to_launch};
 
     #[test]
     fn test_force() {
@@ -243,7 +669,8 @@ mod tests {
 
         let str = include_str!("../../utils/example.json");
 
-        let configs: Vec<PhaseConfig> = serde_json::from_str(str).unwrap();
+        let configs: Vec<PhaseConfig> =
+            config::from_value(serde_json::from_str(str).unwrap()).unwrap();
 
         // Cycle through phases to finally build the last one
         let mut vehicles = Vec::new();
@@ -258,21 +685,29 @@ mod tests {
         for data_point in DATA_POINTS.iter() {
             print!("Testing {} m altitude ... ", data_point.altitude);
 
-            let inertial_to_body = inertial_to_body(data_point.launch, data_point.euler_angles);
+            let inertial_to_body = inertial_to_body(
+                &inertial_to_launch(
+                    data_point.launch[0],
+                    data_point.launch[1],
+                    data_point.launch[2],
+                ),
+                data_point.euler_angles,
+            );
             let vehicle = &vehicles[data_point.vehicle_idx];
+            let state = State {
+                mass: data_point.mass,
+                pressure: data_point.pressure,
+                aero_force_body: data_point.aero_force,
+                ..Default::default()
+            };
 
             assert_almost_eq_rel!(
-                vehicle.auto_throttle(
-                    data_point.max_acceleration,
-                    data_point.mass,
-                    data_point.pressure,
-                    data_point.aero_force
-                ),
+                vehicle.auto_throttle(data_point.max_acceleration, 1., &state),
                 data_point.throttle,
                 EPSILON
             );
             assert_almost_eq_rel!(
-                vehicle.massflow(data_point.throttle),
+                vehicle.massflow(data_point.throttle, &state),
                 data_point.massflow,
                 EPSILON
             );
@@ -281,7 +716,12 @@ mod tests {
                 data_point.propellant_mass,
                 EPSILON
             );
-            assert_almost_eq_rel!(vec vehicle.thrust_force(data_point.throttle, data_point.pressure), data_point.thrust_force, EPSILON);
+            assert_almost_eq_rel!(vec vehicle.thrust_force(data_point.throttle, &state), data_point.thrust_force, EPSILON);
+            assert_almost_eq_rel!(
+                vec(data_point.thrust_force + data_point.aero_force) / data_point.mass,
+                data_point.vehicle_acceleration,
+                EPSILON
+            );
             assert_almost_eq_rel!(
                 Vehicle::alpha(inertial_to_body.transform_vector(&data_point.velocity_planet())),
                 data_point.alpha.to_radians(),
@@ -301,4 +741,187 @@ mod tests {
             println!("ok");
         }
     }
+
+    #[test]
+    fn throttle() {
+        // Throttle bucket over the mach number
+        let throttle: Throttle = serde_json::from_value(serde_json::json!({
+            "x": ["mach_number", [0, 0.8, 1.5, 2]], "data": [1, 0.65, 0.65, 1.04]
+        }))
+        .unwrap();
+        let state = State {
+            mach_number: 1.75,
+            ..Default::default()
+        };
+        assert_almost_eq_rel!(throttle.at_state(&state), 0.845_f64, 1e-12);
+
+        let throttle: Throttle =
+            serde_json::from_value(serde_json::json!(["time", [1, -0.01]])).unwrap();
+        let state = State {
+            time: 30.,
+            ..Default::default()
+        };
+        assert_almost_eq_rel!(throttle.at_state(&state), 0.7_f64, 1e-12);
+        let state = State {
+            time: 200.,
+            ..Default::default()
+        };
+        assert_eq!(throttle.at_state(&state), 0.);
+
+        // The auto-throttle is limited by the commanded throttle
+        let vehicle: Vehicle = {
+            let mut vehicle = Vehicle::default();
+            vehicle.update_with_config(
+                &serde_json::from_value(serde_json::json!({
+                    "engines": [{
+                        "incidence": [0, 0], "thrust_vac": 1e5, "isp_vac": 300, "exit_area": 0
+                    }]
+                }))
+                .unwrap(),
+            );
+            vehicle
+        };
+        let state = State {
+            mass: 1e4,
+            ..Default::default()
+        };
+        let auto_throttle = |max_acceleration, max_throttle| {
+            vehicle.auto_throttle(max_acceleration, max_throttle, &state)
+        };
+        assert_almost_eq_rel!(auto_throttle(f64::INFINITY, 1.04), 1.04_f64, 1e-12);
+        assert_almost_eq_rel!(auto_throttle(5., 1.04), 0.5_f64, 1e-12);
+        assert_almost_eq_rel!(auto_throttle(5., 0.3), 0.3_f64, 1e-12);
+    }
+
+    #[test]
+    fn engine_states() {
+        let mut vehicle = Vehicle::default();
+        vehicle.update_with_config(
+            &serde_json::from_value(serde_json::json!({
+                "engines": [
+                    { "name": "center", "incidence": [0, 0], "thrust_vac": 2e5, "isp_vac": 300, "exit_area": 0 },
+                    { "name": "outer", "incidence": [0, 0], "thrust_vac": 1e5, "isp_vac": 300, "exit_area": 0 },
+                    { "incidence": [0, 0], "thrust_vac": 1e5, "isp_vac": 300, "exit_area": 0, "ignited": false }
+                ]
+            }))
+            .unwrap(),
+        );
+        assert_almost_eq_rel!(
+            vehicle.thrust_force(1., &State::default()).x,
+            3e5_f64,
+            1e-12
+        );
+
+        // Engine failure of the center engine, while the outer engine is
+        // throttled up
+        vehicle.update_with_config(
+            &serde_json::from_value(serde_json::json!({
+                "engine_states": {
+                    "center": { "ignited": false },
+                    "outer": { "throttle": 1.2 }
+                }
+            }))
+            .unwrap(),
+        );
+        assert_almost_eq_rel!(
+            vehicle.thrust_force(0.5, &State::default()).x,
+            6e4_f64,
+            1e-12
+        );
+        assert_almost_eq_rel!(
+            vehicle.massflow(0.5, &State::default()),
+            -6e4 / 300. / STD_GRAVITY,
+            1e-12
+        );
+    }
+
+    #[test]
+    fn engine_tables() {
+        // Solid rocket motor with a thrust curve and a pressure dependent ISP
+        let mut vehicle = Vehicle::default();
+        vehicle.update_with_config(
+            &serde_json::from_value(serde_json::json!({
+                "engines": [{
+                    "incidence": [0, 0],
+                    "thrust_vac": { "x": ["time_since_ignition", [0, 10, 50]], "data": [3e5, 4e5, 2e5] },
+                    "isp_vac": { "x": ["pressure", [0, 1e5]], "data": [280, 250] },
+                    "exit_area": 0.5,
+                    "burn_time": 50
+                }]
+            }))
+            .unwrap(),
+        );
+        vehicle.ignite(20.);
+
+        let state = State {
+            time: 25.,
+            pressure: 5e4,
+            ..Default::default()
+        };
+        assert_almost_eq_rel!(vehicle.thrust_force(1., &state).x, 3.5e5_f64 - 2.5e4, 1e-12);
+        assert_almost_eq_rel!(
+            vehicle.massflow(1., &state),
+            -3.5e5 / 265. / STD_GRAVITY,
+            1e-12
+        );
+
+        // No thrust after the burn time, or once burned out at the burn time
+        assert_eq!(vehicle.burn_time_to_go(&state), Some(45.));
+        let after = State { time: 71., ..state };
+        assert_eq!(vehicle.thrust_force(1., &after), Vector3::zeros());
+        let state = State { time: 70., ..state };
+        assert!(vehicle.burn_out(&state));
+        assert_eq!(vehicle.burn_time_to_go(&state), None);
+        assert_eq!(vehicle.thrust_force(1., &state), Vector3::zeros());
+        assert_eq!(vehicle.massflow(1., &state), 0.);
+
+        // Thrust depending on the throttle of the engine
+        let mut vehicle = Vehicle::default();
+        vehicle.update_with_config(
+            &serde_json::from_value(serde_json::json!({
+                "engines": [{
+                    "incidence": [0, 0],
+                    "thrust_vac": 1e5,
+                    "isp_vac": { "x": ["throttle", [0.5, 1]], "data": [290, 300] },
+                    "exit_area": 0,
+                    "throttle": 0.8
+                }]
+            }))
+            .unwrap(),
+        );
+        assert_almost_eq_rel!(
+            vehicle.massflow(0.75, &State::default()),
+            -0.6e5 / 292. / STD_GRAVITY,
+            1e-12
+        );
+
+        // Performance deck over the throttle, which is not scaled again
+        let mut vehicle = Vehicle::default();
+        vehicle.update_with_config(
+            &serde_json::from_value(serde_json::json!({
+                "engines": [{
+                    "incidence": [0, 0],
+                    "thrust_vac": { "x": ["throttle", [0.5, 1]], "data": [5.5e4, 1e5] },
+                    "isp_vac": 300,
+                    "exit_area": 0.5,
+                    "throttle": 0.8
+                }]
+            }))
+            .unwrap(),
+        );
+        let state = State {
+            pressure: 1e4,
+            ..Default::default()
+        };
+        assert_almost_eq_rel!(
+            vehicle.thrust_force(0.75, &state).x,
+            6.4e4_f64 - 0.6 * 5e3,
+            1e-12
+        );
+        assert_almost_eq_rel!(
+            vehicle.massflow(0.75, &state),
+            -6.4e4 / 300. / STD_GRAVITY,
+            1e-12
+        );
+    }
 }
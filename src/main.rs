@@ -17,16 +17,48 @@
 
 use std::error::Error;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use sim::Simulation;
 
 /// Used to parse the CLI options, which is done by the external `clap` crate.
 #[derive(Parser)]
 #[command(version, about)]
+#[command(subcommand_negates_reqs = true)]
 struct Cli {
     /// The configuration file
-    #[arg(short, long, value_name = "FILE")]
-    config: std::path::PathBuf,
+    #[arg(short, long, value_name = "FILE", required = true)]
+    config: Option<std::path::PathBuf>,
+    /// Alternative commands instead of running the simulation
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// The available subcommands.
+#[derive(Subcommand)]
+enum Command {
+    /// Print the JSON Schema of the configuration file
+    Schema,
+    /// Print the effective configuration of each phase after inheritance
+    Resolve {
+        /// The configuration file
+        #[arg(short, long, value_name = "FILE")]
+        config: std::path::PathBuf,
+    },
+    /// Migrate a configuration file in place to the current format version
+    Migrate {
+        /// The configuration file
+        #[arg(short, long, value_name = "FILE")]
+        config: std::path::PathBuf,
+    },
+    /// Convert a legacy POST namelist input deck into a configuration file
+    Import {
+        /// The POST input deck
+        #[arg(short, long, value_name = "FILE")]
+        input: std::path::PathBuf,
+        /// The configuration file to write. Prints to stdout if omitted
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+    },
 }
 
 /// The main entry point for the CLI.
@@ -38,9 +70,43 @@ struct Cli {
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Cli::parse();
 
-    let sim = Simulation::from_file(args.config)?;
+    match args.command {
+        Some(Command::Schema) => {
+            println!("{}", serde_json::to_string_pretty(&Simulation::schema())?);
+        }
+        Some(Command::Resolve { config }) => {
+            let sim = Simulation::from_file(config)?;
+
+            println!("{}", serde_json::to_string_pretty(&sim.resolve())?);
+        }
+        Some(Command::Migrate { config }) => match Simulation::migrate_file(&config)? {
+            Some(version) => println!(
+                "Migrated {} from version {version} to version {}",
+                config.display(),
+                sim::CONFIG_VERSION
+            ),
+            None => println!("{} is already up to date", config.display()),
+        },
+        Some(Command::Import { input, output }) => {
+            let deck = sim::import_deck(&std::fs::read_to_string(input)?)?;
+
+            for unsupported in deck.unsupported.iter() {
+                eprintln!("Not imported: {unsupported}");
+            }
+
+            let config = serde_json::to_string_pretty(&deck.config)?;
+            match output {
+                Some(output) => std::fs::write(output, config + "\n")?,
+                None => println!("{config}"),
+            }
+        }
+        None => {
+            let config = args.config.expect("clap requires the config argument");
+            let sim = Simulation::from_file(config)?;
 
-    sim.run();
+            sim.run();
+        }
+    }
 
     Ok(())
 }
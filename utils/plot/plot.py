@@ -53,7 +53,7 @@ def parse_test_output(content: str) -> pd.DataFrame:
         pd.DataFrame: The data.
     """
     # Define regular expressions for extracting each data point
-    phase_pattern = re.compile(r"Starting Phase (\d+)")
+    phase_pattern = re.compile(r"Starting Phase (.+)")
     time_pattern = re.compile(r"Time: (\d+)")
     position_pattern = re.compile(
         r"Position:[\S\s]*?\│\s+(-?\d+)[\S\s]*?\│\s+(-?\d+)[\S\s]*?\│\s+(-?\d+)"